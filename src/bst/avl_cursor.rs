@@ -0,0 +1,237 @@
+use crate::bst::avl_node::{Node, NodePtr};
+use crate::bst::avl_tree::AvlTree;
+use crate::bst::comparator::Comparator;
+use crate::bst::duplicate_policy::DuplicatePolicy;
+use crate::bst::summary::Summary;
+use crate::bst::tree_error::TreeError;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+// A position in an AvlTree that steps one node at a time in sorted order. Besides the nodes
+// there is a ghost position, holding None, sitting after the last node and before the first,
+// so moving past either end wraps around through it
+pub struct Cursor<'a, K, V, C, S = ()> {
+    tree: &'a AvlTree<K, V, C, S>,
+    current: Option<NodePtr<K, V, S>>,
+}
+
+// A cursor that can also insert next to its position and remove the node it points at
+pub struct CursorMut<'a, K, V, C, S = ()> {
+    tree: &'a mut AvlTree<K, V, C, S>,
+    current: Option<NodePtr<K, V, S>>,
+}
+
+impl<K, V, C, S> AvlTree<K, V, C, S> {
+    pub fn cursor_front(&self) -> Cursor<'_, K, V, C, S> {
+        let current = self.first_node();
+        Cursor {
+            tree: self,
+            current,
+        }
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, K, V, C, S> {
+        let current = self.last_node();
+        Cursor {
+            tree: self,
+            current,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, K, V, C, S> {
+        let current = self.first_node();
+        CursorMut {
+            tree: self,
+            current,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, K, V, C, S> {
+        let current = self.last_node();
+        CursorMut {
+            tree: self,
+            current,
+        }
+    }
+
+    fn node_after(&self, position: &Option<NodePtr<K, V, S>>) -> Option<NodePtr<K, V, S>> {
+        match position {
+            Some(node_ptr) => Self::get_next_node(node_ptr),
+            None => self.first_node(),
+        }
+    }
+
+    fn node_before(&self, position: &Option<NodePtr<K, V, S>>) -> Option<NodePtr<K, V, S>> {
+        match position {
+            Some(node_ptr) => Self::get_previous_node(node_ptr),
+            None => self.last_node(),
+        }
+    }
+}
+
+impl<K, V, C: Comparator<K>, S: Summary<K, V>> AvlTree<K, V, C, S> {
+    // Cursor at the earliest node not below key, or at the ghost position when there is none
+    pub fn lower_bound(&self, key: &K) -> Cursor<'_, K, V, C, S> {
+        let current = self.ceiling(key);
+        Cursor {
+            tree: self,
+            current,
+        }
+    }
+
+    // Cursor at the earliest node above key, or at the ghost position when there is none
+    pub fn upper_bound(&self, key: &K) -> Cursor<'_, K, V, C, S> {
+        let current = self.successor(key);
+        Cursor {
+            tree: self,
+            current,
+        }
+    }
+
+    pub fn lower_bound_mut(&mut self, key: &K) -> CursorMut<'_, K, V, C, S> {
+        let current = self.ceiling(key);
+        CursorMut {
+            tree: self,
+            current,
+        }
+    }
+
+    pub fn upper_bound_mut(&mut self, key: &K) -> CursorMut<'_, K, V, C, S> {
+        let current = self.successor(key);
+        CursorMut {
+            tree: self,
+            current,
+        }
+    }
+}
+
+impl<'a, K, V, C, S> Cursor<'a, K, V, C, S> {
+    // None at the ghost position
+    pub fn current(&self) -> Option<NodePtr<K, V, S>> {
+        self.current.as_ref().map(Rc::clone)
+    }
+
+    pub fn move_next(&mut self) {
+        self.current = self.tree.node_after(&self.current);
+    }
+
+    pub fn move_prev(&mut self) {
+        self.current = self.tree.node_before(&self.current);
+    }
+
+    pub fn peek_next(&self) -> Option<NodePtr<K, V, S>> {
+        self.tree.node_after(&self.current)
+    }
+
+    pub fn peek_prev(&self) -> Option<NodePtr<K, V, S>> {
+        self.tree.node_before(&self.current)
+    }
+}
+
+impl<'a, K, V, C, S> CursorMut<'a, K, V, C, S> {
+    // None at the ghost position
+    pub fn current(&self) -> Option<NodePtr<K, V, S>> {
+        self.current.as_ref().map(Rc::clone)
+    }
+
+    pub fn move_next(&mut self) {
+        self.current = self.tree.node_after(&self.current);
+    }
+
+    pub fn move_prev(&mut self) {
+        self.current = self.tree.node_before(&self.current);
+    }
+
+    pub fn peek_next(&self) -> Option<NodePtr<K, V, S>> {
+        self.tree.node_after(&self.current)
+    }
+
+    pub fn peek_prev(&self) -> Option<NodePtr<K, V, S>> {
+        self.tree.node_before(&self.current)
+    }
+
+    // Read-only view of the same position
+    pub fn as_cursor(&self) -> Cursor<'_, K, V, C, S> {
+        Cursor {
+            tree: self.tree,
+            current: self.current(),
+        }
+    }
+}
+
+impl<'a, K, V, C: Comparator<K>, S: Summary<K, V>> CursorMut<'a, K, V, C, S> {
+    // Inserts node between the previous position and the current one, leaving the cursor
+    // where it is. Equal neighbours are only allowed under DuplicatePolicy::KeepAll, since the
+    // other policies keep a single node per key
+    pub fn insert_before(&mut self, node: Node<K, V, S>) -> Result<(), TreeError> {
+        let previous_node = self.peek_prev();
+        self.check_order(previous_node.as_ref(), &node, self.current.as_ref())?;
+        let position = match &self.current {
+            Some(current_ptr) => match current_ptr.borrow().get_left_child() {
+                Some(left_child_ptr) => Some((
+                    AvlTree::<K, V, C, S>::get_maximum_child(left_child_ptr),
+                    false,
+                )),
+                None => Some((Rc::clone(current_ptr), true)),
+            },
+            None => previous_node.map(|last_ptr| (last_ptr, false)),
+        };
+        self.tree.attach_node(position, node);
+        Ok(())
+    }
+
+    // Inserts node between the current position and the next one, leaving the cursor where
+    // it is
+    pub fn insert_after(&mut self, node: Node<K, V, S>) -> Result<(), TreeError> {
+        let next_node = self.peek_next();
+        self.check_order(self.current.as_ref(), &node, next_node.as_ref())?;
+        let position = match &self.current {
+            Some(current_ptr) => match current_ptr.borrow().get_right_child() {
+                Some(right_child_ptr) => Some((
+                    AvlTree::<K, V, C, S>::get_minimum_child(right_child_ptr),
+                    true,
+                )),
+                None => Some((Rc::clone(current_ptr), false)),
+            },
+            None => next_node.map(|first_ptr| (first_ptr, true)),
+        };
+        self.tree.attach_node(position, node);
+        Ok(())
+    }
+
+    // Takes one entry of the current node out of the tree like AvlTree::remove, moving on to the
+    // next node once the current one is gone. Does nothing at the ghost position, and returns
+    // None without moving when the node is still held through a NodePtr elsewhere
+    pub fn remove_current(&mut self) -> Option<(V, K)> {
+        let current_ptr = self.current.take()?;
+        let next_node = AvlTree::<K, V, C, S>::get_next_node(&current_ptr);
+        let weak_current_ptr = Rc::downgrade(&current_ptr);
+        let entry = self.tree.take_entry(current_ptr).ok();
+        // The node only outlives the call when it kept part of its count or was left in place
+        self.current = weak_current_ptr.upgrade().or(next_node);
+        entry
+    }
+
+    fn check_order(
+        &self,
+        lower: Option<&NodePtr<K, V, S>>,
+        node: &Node<K, V, S>,
+        upper: Option<&NodePtr<K, V, S>>,
+    ) -> Result<(), TreeError> {
+        let comparator = self.tree.comparator();
+        let lower_ordering =
+            lower.map(|lower_ptr| comparator.compare(&lower_ptr.borrow().value, &node.value));
+        let upper_ordering =
+            upper.map(|upper_ptr| comparator.compare(&node.value, &upper_ptr.borrow().value));
+        let orderings = [lower_ordering, upper_ordering];
+        if orderings.contains(&Some(Ordering::Greater)) {
+            return Err(TreeError::OrderViolation);
+        }
+        if orderings.contains(&Some(Ordering::Equal))
+            && self.tree.duplicate_policy() != DuplicatePolicy::KeepAll
+        {
+            return Err(TreeError::DuplicateKey);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+#[cfg(test)]
+mod tests {
+    use super::super::avl_node::*;
+    use super::super::avl_tree::*;
+    use super::super::duplicate_policy::DuplicatePolicy;
+    use super::super::tree_error::TreeError;
+
+    fn value_of(node_ptr: Option<IsizeNodePtr>) -> Option<isize> {
+        node_ptr.map(|node_ptr| node_ptr.borrow().value)
+    }
+
+    fn values(tree: &IsizeAvlTree) -> Vec<isize> {
+        tree.iter().map(|entry| *entry.value()).collect()
+    }
+
+    fn assert_balanced(tree: &IsizeAvlTree) {
+        assert!(tree
+            .level_order()
+            .all(|view| view.balance_factor.abs() <= 1));
+        assert_eq!(tree.len(), tree.level_order().count());
+    }
+
+    #[test]
+    fn test_move_next_and_prev() {
+        let tree = build_free_from_values(vec![3, 1, 2]);
+        let mut cursor = tree.cursor_front();
+        assert_eq!(value_of(cursor.current()), Some(1));
+        cursor.move_next();
+        assert_eq!(value_of(cursor.current()), Some(2));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(value_of(cursor.current()), None);
+        cursor.move_next();
+        assert_eq!(value_of(cursor.current()), Some(1));
+        cursor.move_prev();
+        assert_eq!(value_of(cursor.current()), None);
+        cursor.move_prev();
+        assert_eq!(value_of(cursor.current()), Some(3));
+    }
+
+    #[test]
+    fn test_peek() {
+        let tree = build_free_from_values(vec![10, 20, 30]);
+        let mut cursor = tree.cursor_back();
+        assert_eq!(value_of(cursor.peek_prev()), Some(20));
+        assert_eq!(value_of(cursor.peek_next()), None);
+        cursor.move_next();
+        assert_eq!(value_of(cursor.peek_next()), Some(10));
+        assert_eq!(value_of(cursor.peek_prev()), Some(30));
+    }
+
+    #[test]
+    fn test_cursor_on_empty_tree() {
+        let mut tree = IsizeAvlTree::new();
+        let mut cursor = tree.cursor_front();
+        cursor.move_next();
+        assert!(cursor.current().is_none());
+        assert!(cursor.peek_prev().is_none());
+        let mut cursor = tree.cursor_back_mut();
+        assert_eq!(cursor.remove_current(), None);
+        cursor.insert_before(Node::new(0, 5)).unwrap();
+        assert!(cursor.current().is_none());
+        assert_eq!(values(&tree), vec![5]);
+    }
+
+    #[test]
+    fn test_insert_before_and_after() {
+        let mut tree = build_free_from_values(vec![10, 20, 30]);
+        let mut cursor = tree.cursor_front_mut();
+        cursor.move_next();
+        cursor.insert_before(Node::new(0, 15)).unwrap();
+        cursor.insert_after(Node::new(0, 25)).unwrap();
+        assert_eq!(value_of(cursor.current()), Some(20));
+        assert_eq!(value_of(cursor.peek_prev()), Some(15));
+        assert_eq!(value_of(cursor.peek_next()), Some(25));
+        assert_eq!(values(&tree), vec![10, 15, 20, 25, 30]);
+        assert_balanced(&tree);
+    }
+
+    #[test]
+    fn test_insert_at_ghost_position() {
+        let mut tree = build_free_from_values(vec![10, 20]);
+        let mut cursor = tree.cursor_back_mut();
+        cursor.move_next();
+        cursor.insert_before(Node::new(0, 30)).unwrap();
+        cursor.insert_after(Node::new(0, 5)).unwrap();
+        assert!(cursor.current().is_none());
+        assert_eq!(values(&tree), vec![5, 10, 20, 30]);
+    }
+
+    #[test]
+    fn test_insert_out_of_order() {
+        let mut tree = build_free_from_values(vec![10, 20, 30]);
+        let mut cursor = tree.cursor_front_mut();
+        cursor.move_next();
+        assert_eq!(
+            cursor.insert_before(Node::new(0, 25)),
+            Err(TreeError::OrderViolation)
+        );
+        assert_eq!(
+            cursor.insert_after(Node::new(0, 5)),
+            Err(TreeError::OrderViolation)
+        );
+        assert_eq!(values(&tree), vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn test_insert_equal_neighbour() {
+        let mut tree = build_free_from_values(vec![10, 20]);
+        let mut cursor = tree.cursor_front_mut();
+        cursor.insert_after(Node::new(1, 10)).unwrap();
+        assert_eq!(values(&tree), vec![10, 10, 20]);
+
+        let mut tree =
+            build_free_from_values(vec![10, 20]).with_duplicate_policy(DuplicatePolicy::Reject);
+        let mut cursor = tree.cursor_front_mut();
+        assert_eq!(
+            cursor.insert_after(Node::new(1, 10)),
+            Err(TreeError::DuplicateKey)
+        );
+    }
+
+    #[test]
+    fn test_many_inserts_stay_balanced() {
+        let mut tree = IsizeAvlTree::new();
+        let mut cursor = tree.cursor_front_mut();
+        for value in 100..200 {
+            cursor.insert_before(Node::new(0, value)).unwrap();
+        }
+        for value in (0..100).rev() {
+            cursor.insert_after(Node::new(0, value)).unwrap();
+        }
+        assert_eq!(values(&tree), (0..200).collect::<Vec<_>>());
+        assert_balanced(&tree);
+    }
+
+    #[test]
+    fn test_remove_current() {
+        let mut tree = build_free_from_values((1..=7).collect());
+        let mut cursor = tree.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        // The root of this tree has two children
+        assert_eq!(cursor.remove_current(), Some((0, 4)));
+        assert_eq!(value_of(cursor.current()), Some(5));
+        assert_eq!(cursor.remove_current(), Some((0, 5)));
+        assert_eq!(value_of(cursor.current()), Some(6));
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some((0, 7)));
+        assert!(cursor.current().is_none());
+        assert_eq!(value_of(cursor.peek_prev()), Some(6));
+        assert_eq!(values(&tree), vec![1, 2, 3, 6]);
+        assert_balanced(&tree);
+    }
+
+    #[test]
+    fn test_remove_current_takes_one_counted_entry() {
+        let mut tree = IsizeAvlTree::new().with_duplicate_policy(DuplicatePolicy::Count);
+        for value in [1, 2, 2, 3] {
+            tree.insert(Node::new(0, value)).unwrap();
+        }
+        let mut cursor = tree.cursor_front_mut();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some((0, 2)));
+        assert_eq!(value_of(cursor.current()), Some(2));
+        assert_eq!(cursor.remove_current(), Some((0, 2)));
+        assert_eq!(value_of(cursor.current()), Some(3));
+        assert_eq!(values(&tree), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_remove_current_while_node_ptr_is_held() {
+        let mut tree = build_free_from_values(vec![1, 2, 3]);
+        let node_found = tree.search_tree_by_value(&2).unwrap();
+        let mut cursor = tree.cursor_front_mut();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), None);
+        assert_eq!(value_of(cursor.current()), Some(2));
+        drop(node_found);
+        assert_eq!(cursor.remove_current(), Some((0, 2)));
+        assert_eq!(value_of(cursor.current()), Some(3));
+    }
+
+    #[test]
+    fn test_remove_everything_through_cursor() {
+        let mut tree = build_free_from_values((0..50).collect());
+        let mut cursor = tree.cursor_front_mut();
+        for value in 0..50 {
+            assert_eq!(cursor.remove_current(), Some((0, value)));
+        }
+        assert!(cursor.current().is_none());
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn test_as_cursor() {
+        let mut tree = build_free_from_values(vec![1, 2]);
+        let cursor = tree.cursor_back_mut();
+        assert_eq!(value_of(cursor.as_cursor().peek_prev()), Some(1));
+    }
+
+    #[test]
+    fn test_lower_and_upper_bound() {
+        let tree = build_free_from_values(vec![10, 20, 20, 30]);
+        let mut cursor = tree.lower_bound(&20);
+        assert_eq!(value_of(cursor.current()), Some(20));
+        assert_eq!(value_of(cursor.peek_prev()), Some(10));
+        cursor.move_next();
+        assert_eq!(value_of(cursor.current()), Some(20));
+        let cursor = tree.upper_bound(&20);
+        assert_eq!(value_of(cursor.current()), Some(30));
+        assert_eq!(value_of(tree.lower_bound(&15).current()), Some(20));
+        assert!(tree.upper_bound(&30).current().is_none());
+        assert_eq!(value_of(tree.upper_bound(&30).peek_prev()), Some(30));
+    }
+
+    #[test]
+    fn test_bound_cursor_insert() {
+        let mut tree = build_free_from_values(vec![10, 30]);
+        let mut cursor = tree.upper_bound_mut(&15);
+        cursor.insert_before(Node::new(0, 20)).unwrap();
+        let mut cursor = tree.lower_bound_mut(&31);
+        assert!(cursor.current().is_none());
+        cursor.insert_before(Node::new(0, 40)).unwrap();
+        assert_eq!(values(&tree), vec![10, 20, 30, 40]);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::bst::avl_node::{Node, NodePtr};
+use crate::bst::avl_tree::AvlTree;
+use crate::bst::comparator::Comparator;
+use crate::bst::summary::Summary;
+use crate::bst::tree_error::TreeError;
+use std::cell::{Ref, RefMut};
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+
+// A single position in an AvlTree found by one descent, either holding a node for the key or
+// the spot where a node for it would be attached
+pub enum Entry<'a, K, V, C, S = ()> {
+    Occupied(OccupiedEntry<'a, K, V, C, S>),
+    Vacant(VacantEntry<'a, K, V, C, S>),
+}
+
+pub struct OccupiedEntry<'a, K, V, C, S = ()> {
+    pub(crate) tree: &'a mut AvlTree<K, V, C, S>,
+    pub(crate) node_ptr: NodePtr<K, V, S>,
+}
+
+pub struct VacantEntry<'a, K, V, C, S = ()> {
+    pub(crate) tree: &'a mut AvlTree<K, V, C, S>,
+    pub(crate) value: K,
+    pub(crate) position: Option<(NodePtr<K, V, S>, bool)>,
+}
+
+// Write access to the index of an occupied entry; once it is dropped the sizes and summaries
+// above the node are refreshed, so they can depend on the index
+pub struct IndexRefMut<'b, K, V, S: Summary<K, V>> {
+    index: Option<RefMut<'b, V>>,
+    node_ptr: &'b NodePtr<K, V, S>,
+}
+
+impl<'a, K, V, C: Comparator<K>, S: Summary<K, V>> Entry<'a, K, V, C, S> {
+    pub fn or_insert(self, index: V) -> OccupiedEntry<'a, K, V, C, S> {
+        match self {
+            Entry::Occupied(entry) => entry,
+            Entry::Vacant(entry) => entry.insert(index),
+        }
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> OccupiedEntry<'a, K, V, C, S> {
+        match self {
+            Entry::Occupied(entry) => entry,
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(self, modify: F) -> Entry<'a, K, V, C, S> {
+        match self {
+            Entry::Occupied(mut entry) => {
+                modify(&mut entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+impl<'a, K, V, C: Comparator<K>, S: Summary<K, V>> OccupiedEntry<'a, K, V, C, S> {
+    pub fn key(&self) -> Ref<'_, K> {
+        Ref::map(self.node_ptr.borrow(), |node| &node.value)
+    }
+
+    pub fn get(&self) -> Ref<'_, V> {
+        Ref::map(self.node_ptr.borrow(), |node| &node.index)
+    }
+
+    pub fn get_mut(&mut self) -> IndexRefMut<'_, K, V, S> {
+        IndexRefMut {
+            index: Some(RefMut::map(self.node_ptr.borrow_mut(), |node| {
+                &mut node.index
+            })),
+            node_ptr: &self.node_ptr,
+        }
+    }
+
+    // Replaces the index, returning the old one
+    pub fn insert(&mut self, index: V) -> V {
+        std::mem::replace(&mut *self.get_mut(), index)
+    }
+
+    // Takes one entry out of the tree like AvlTree::remove, leaving the tree as it was when
+    // the node is still held through a NodePtr elsewhere
+    pub fn remove(self) -> Result<(V, K), TreeError> {
+        self.tree.take_entry(self.node_ptr)
+    }
+}
+
+impl<'a, K, V, C: Comparator<K>, S: Summary<K, V>> VacantEntry<'a, K, V, C, S> {
+    pub fn key(&self) -> &K {
+        &self.value
+    }
+
+    pub fn into_key(self) -> K {
+        self.value
+    }
+
+    pub fn insert(self, index: V) -> OccupiedEntry<'a, K, V, C, S> {
+        let node_ptr = self
+            .tree
+            .attach_node(self.position, Node::new(index, self.value));
+        OccupiedEntry {
+            tree: self.tree,
+            node_ptr,
+        }
+    }
+}
+
+impl<'b, K, V, S: Summary<K, V>> Deref for IndexRefMut<'b, K, V, S> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        self.index.as_ref().unwrap()
+    }
+}
+
+impl<'b, K, V, S: Summary<K, V>> DerefMut for IndexRefMut<'b, K, V, S> {
+    fn deref_mut(&mut self) -> &mut V {
+        self.index.as_mut().unwrap()
+    }
+}
+
+impl<'b, K, V, S: Summary<K, V>> Drop for IndexRefMut<'b, K, V, S> {
+    fn drop(&mut self) {
+        // The node has to be released before its summary can be recomputed
+        self.index = None;
+        Node::refresh_upwards(Rc::clone(self.node_ptr));
+    }
+}
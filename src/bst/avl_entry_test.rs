@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    use super::super::avl_entry::*;
+    use super::super::avl_tree::*;
+    use super::super::tree_error::TreeError;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    fn setup_tree() -> AvlTree<isize, isize> {
+        build_free_from_values(vec![30, 50, 70, 65, 68])
+    }
+
+    #[test]
+    fn test_entry_occupied() {
+        let mut tree = setup_tree();
+        match tree.entry(65) {
+            Entry::Occupied(entry) => assert_eq!(*entry.key(), 65),
+            Entry::Vacant(_) => panic!("65 is in the tree"),
+        }
+    }
+
+    #[test]
+    fn test_entry_vacant() {
+        let mut tree = setup_tree();
+        match tree.entry(66) {
+            Entry::Occupied(_) => panic!("66 is not in the tree"),
+            Entry::Vacant(entry) => assert_eq!(*entry.key(), 66),
+        }
+    }
+
+    #[test]
+    fn test_or_insert_on_vacant_rebalances() {
+        let mut tree = setup_tree();
+        let entry = tree.entry(75).or_insert(7);
+        assert_eq!(*entry.get(), 7);
+        assert_eq!(
+            convert_node_to_vec(Rc::clone(tree.root.as_ref().unwrap())),
+            VecDeque::from(vec![68, 50, 70, 30, 65, 75])
+        );
+    }
+
+    #[test]
+    fn test_or_insert_on_empty_tree() {
+        let mut tree: AvlTree<isize, isize> = AvlTree::new();
+        tree.entry(1).or_insert(10);
+        assert_eq!(tree.search_tree_by_value(&1).unwrap().borrow().index, 10);
+    }
+
+    #[test]
+    fn test_or_insert_keeps_existing() {
+        let mut tree = setup_tree();
+        let entry = tree.entry(70).or_insert(7);
+        assert_eq!(*entry.get(), 0);
+    }
+
+    #[test]
+    fn test_or_insert_with() {
+        let mut tree = setup_tree();
+        tree.entry(10).or_insert_with(|| 100);
+        tree.entry(10)
+            .or_insert_with(|| panic!("10 is already in the tree"));
+        assert_eq!(tree.search_tree_by_value(&10).unwrap().borrow().index, 100);
+    }
+
+    #[test]
+    fn test_and_modify_counts() {
+        let mut tree: AvlTree<char, usize> = AvlTree::new();
+        for letter in "mississippi".chars() {
+            tree.entry(letter)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+        let count_of = |letter: char| tree.search_tree_by_value(&letter).unwrap().borrow().index;
+        assert_eq!(count_of('m'), 1);
+        assert_eq!(count_of('i'), 4);
+        assert_eq!(count_of('s'), 4);
+        assert_eq!(count_of('p'), 2);
+    }
+
+    #[test]
+    fn test_occupied_insert_and_get_mut() {
+        let mut tree = setup_tree();
+        if let Entry::Occupied(mut entry) = tree.entry(50) {
+            assert_eq!(entry.insert(5), 0);
+            *entry.get_mut() += 1;
+        }
+        assert_eq!(tree.search_tree_by_value(&50).unwrap().borrow().index, 6);
+    }
+
+    #[test]
+    fn test_occupied_remove() {
+        let mut tree = setup_tree();
+        tree.search_tree_by_value(&50).unwrap().borrow_mut().index = 5;
+        if let Entry::Occupied(entry) = tree.entry(50) {
+            assert_eq!(entry.remove(), Ok((5, 50)));
+        }
+        assert_eq!(
+            convert_node_to_vec(tree.root.unwrap()),
+            VecDeque::from(vec![65, 30, 68, 70])
+        );
+    }
+
+    #[test]
+    fn test_occupied_remove_while_node_ptr_is_held() {
+        let mut tree = setup_tree();
+        let node_found = tree.search_tree_by_value(&65).unwrap();
+        if let Entry::Occupied(entry) = tree.entry(65) {
+            assert_eq!(entry.remove(), Err(TreeError::NodeInUse));
+        }
+        assert_eq!(tree.len(), 5);
+        drop(node_found);
+        if let Entry::Occupied(entry) = tree.entry(65) {
+            assert_eq!(entry.remove(), Ok((0, 65)));
+        }
+        assert_eq!(tree.len(), 4);
+    }
+
+    #[test]
+    fn test_vacant_into_key() {
+        let mut tree: AvlTree<String, isize> = AvlTree::new();
+        if let Entry::Vacant(entry) = tree.entry("key".to_string()) {
+            assert_eq!(entry.into_key(), "key");
+        }
+        assert!(tree.root.is_none());
+    }
+}
@@ -0,0 +1,299 @@
+use crate::bst::avl_entry;
+use crate::bst::avl_node::NodePtr;
+use crate::bst::avl_tree::{self, AvlTree};
+use crate::bst::comparator::{Comparator, NaturalOrder};
+use std::cell::{Ref, RefMut};
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+use std::rc::Rc;
+
+// Map facade over AvlTree: a map key is stored as the node value and a map value as the node
+// index, so keys stay unique and are ordered by the tree's comparator. The methods follow
+// BTreeMap, except that entries live in RefCell nodes which can't lend plain references:
+// get, get_mut, first_key_value, last_key_value, iter and range hand out MapRef or MapRefMut
+// guards instead, whose key and value borrow the node, so a BTreeMap caller writes
+// *map.get(&key)?.value() where it wrote *map.get(&key)?
+#[derive(Debug)]
+pub struct AvlMap<K, V, C = NaturalOrder> {
+    tree: AvlTree<K, V, C>,
+}
+
+// Read access to one entry of an AvlMap, borrowing the map so it can't be changed meanwhile
+pub struct MapRef<'a, K, V> {
+    node_ptr: NodePtr<K, V>,
+    marker: PhantomData<&'a ()>,
+}
+
+// Write access to the value of one entry of an AvlMap
+pub struct MapRefMut<'a, K, V> {
+    node_ptr: NodePtr<K, V>,
+    marker: PhantomData<&'a mut ()>,
+}
+
+impl<'a, K, V> MapRef<'a, K, V> {
+    fn new(node_ptr: NodePtr<K, V>) -> MapRef<'a, K, V> {
+        MapRef {
+            node_ptr,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn key(&self) -> Ref<'_, K> {
+        Ref::map(self.node_ptr.borrow(), |node| &node.value)
+    }
+
+    pub fn value(&self) -> Ref<'_, V> {
+        Ref::map(self.node_ptr.borrow(), |node| &node.index)
+    }
+}
+
+impl<'a, K, V> MapRefMut<'a, K, V> {
+    fn new(node_ptr: NodePtr<K, V>) -> MapRefMut<'a, K, V> {
+        MapRefMut {
+            node_ptr,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn key(&self) -> Ref<'_, K> {
+        Ref::map(self.node_ptr.borrow(), |node| &node.value)
+    }
+
+    pub fn value(&self) -> Ref<'_, V> {
+        Ref::map(self.node_ptr.borrow(), |node| &node.index)
+    }
+
+    pub fn value_mut(&mut self) -> RefMut<'_, V> {
+        RefMut::map(self.node_ptr.borrow_mut(), |node| &mut node.index)
+    }
+}
+
+// Walks the entries of an AvlMap in ascending key order, from either end
+pub struct Iter<'a, K, V> {
+    inner: avl_tree::Iter<'a, K, V>,
+}
+
+// Entries of an AvlMap whose keys fall between two bounds, in ascending key order
+pub struct Range<'a, K, V> {
+    inner: avl_tree::Range<'a, K, V>,
+}
+
+// Entry API of AvlMap, a thin layer over the tree's entries that hands removed values back
+// without cloning them
+pub enum Entry<'a, K, V, C> {
+    Occupied(OccupiedEntry<'a, K, V, C>),
+    Vacant(VacantEntry<'a, K, V, C>),
+}
+
+pub struct OccupiedEntry<'a, K, V, C> {
+    inner: avl_entry::OccupiedEntry<'a, K, V, C>,
+}
+
+pub struct VacantEntry<'a, K, V, C> {
+    inner: avl_entry::VacantEntry<'a, K, V, C>,
+}
+
+impl<K, V, C: Default> Default for AvlMap<K, V, C> {
+    fn default() -> Self {
+        AvlMap::with_comparator(C::default())
+    }
+}
+
+impl<K: Ord, V> AvlMap<K, V> {
+    pub fn new() -> AvlMap<K, V> {
+        AvlMap::with_comparator(NaturalOrder)
+    }
+}
+
+impl<K, V, C> AvlMap<K, V, C> {
+    pub fn with_comparator(comparator: C) -> AvlMap<K, V, C> {
+        AvlMap {
+            tree: AvlTree::with_comparator(comparator),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    pub fn first_key_value(&self) -> Option<MapRef<'_, K, V>> {
+        let root = Rc::clone(self.tree.root.as_ref()?);
+        Some(MapRef::new(AvlTree::<K, V, C>::get_minimum_child(root)))
+    }
+
+    pub fn last_key_value(&self) -> Option<MapRef<'_, K, V>> {
+        let root = Rc::clone(self.tree.root.as_ref()?);
+        Some(MapRef::new(AvlTree::<K, V, C>::get_maximum_child(root)))
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.tree.iter(),
+        }
+    }
+}
+
+impl<K, V, C: Comparator<K>> AvlMap<K, V, C> {
+    // Returns the value previously stored under key, keeping the original key
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<MapRef<'_, K, V>> {
+        self.tree.search_tree_by_value(key).map(MapRef::new)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<MapRefMut<'_, K, V>> {
+        self.tree.search_tree_by_value(key).map(MapRefMut::new)
+    }
+
+    // Panics on the same bounds BTreeMap::range does
+    pub fn range<R: RangeBounds<K>>(&self, bounds: R) -> Range<'_, K, V> {
+        Range {
+            inner: self.tree.range(bounds),
+        }
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.tree.search_tree_by_value(key).is_some()
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (value, _) = self.tree.remove(key)?;
+        Some(value)
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, C> {
+        match self.tree.entry(key) {
+            avl_entry::Entry::Occupied(inner) => Entry::Occupied(OccupiedEntry { inner }),
+            avl_entry::Entry::Vacant(inner) => Entry::Vacant(VacantEntry { inner }),
+        }
+    }
+}
+
+impl<'a, K, V, C: Comparator<K>> Entry<'a, K, V, C> {
+    pub fn or_insert(self, value: V) -> OccupiedEntry<'a, K, V, C> {
+        match self {
+            Entry::Occupied(entry) => entry,
+            Entry::Vacant(entry) => entry.insert(value),
+        }
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> OccupiedEntry<'a, K, V, C> {
+        match self {
+            Entry::Occupied(entry) => entry,
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(self, modify: F) -> Entry<'a, K, V, C> {
+        match self {
+            Entry::Occupied(mut entry) => {
+                modify(&mut entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+impl<'a, K, V, C: Comparator<K>> OccupiedEntry<'a, K, V, C> {
+    pub fn key(&self) -> Ref<'_, K> {
+        self.inner.key()
+    }
+
+    pub fn get(&self) -> Ref<'_, V> {
+        self.inner.get()
+    }
+
+    // Maps carry no summary, so the index can be handed out without a refreshing guard
+    pub fn get_mut(&mut self) -> RefMut<'_, V> {
+        RefMut::map(self.inner.node_ptr.borrow_mut(), |node| &mut node.index)
+    }
+
+    // Replaces the value, returning the old one
+    pub fn insert(&mut self, value: V) -> V {
+        self.inner.insert(value)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        // Map guards and entries borrow the map, so nothing outside it can still hold the node
+        let (value, key) = self
+            .inner
+            .remove()
+            .expect("map nodes are only held by the map");
+        (key, value)
+    }
+}
+
+impl<'a, K, V, C: Comparator<K>> VacantEntry<'a, K, V, C> {
+    pub fn key(&self) -> &K {
+        self.inner.key()
+    }
+
+    pub fn into_key(self) -> K {
+        self.inner.into_key()
+    }
+
+    pub fn insert(self, value: V) -> OccupiedEntry<'a, K, V, C> {
+        OccupiedEntry {
+            inner: self.inner.insert(value),
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = MapRef<'a, K, V>;
+
+    fn next(&mut self) -> Option<MapRef<'a, K, V>> {
+        self.inner
+            .next()
+            .map(|entry| MapRef::new(entry.into_node_ptr()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<MapRef<'a, K, V>> {
+        self.inner
+            .next_back()
+            .map(|entry| MapRef::new(entry.into_node_ptr()))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = MapRef<'a, K, V>;
+
+    fn next(&mut self) -> Option<MapRef<'a, K, V>> {
+        self.inner
+            .next()
+            .map(|entry| MapRef::new(entry.into_node_ptr()))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Range<'a, K, V> {
+    fn next_back(&mut self) -> Option<MapRef<'a, K, V>> {
+        self.inner
+            .next_back()
+            .map(|entry| MapRef::new(entry.into_node_ptr()))
+    }
+}
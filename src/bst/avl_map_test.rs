@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod tests {
+    use super::super::avl_map::*;
+    use super::super::comparator::ReverseOrder;
+
+    fn setup_map() -> AvlMap<isize, &'static str> {
+        let mut map = AvlMap::new();
+        map.insert(30, "thirty");
+        map.insert(50, "fifty");
+        map.insert(70, "seventy");
+        map.insert(65, "sixty-five");
+        map.insert(68, "sixty-eight");
+        map
+    }
+
+    #[test]
+    fn test_empty_map() {
+        let map: AvlMap<isize, isize> = AvlMap::new();
+        assert!(map.is_empty());
+        assert_eq!(map.len(), 0);
+        assert!(map.get(&1).is_none());
+        assert!(map.first_key_value().is_none());
+        assert!(map.last_key_value().is_none());
+    }
+
+    #[test]
+    fn test_insert_new_key() {
+        let mut map = AvlMap::new();
+        assert_eq!(map.insert(1, "one"), None);
+        assert_eq!(map.len(), 1);
+        assert!(map.contains_key(&1));
+    }
+
+    #[test]
+    fn test_insert_existing_key_returns_previous_value() {
+        let mut map = setup_map();
+        assert_eq!(map.insert(65, "LXV"), Some("sixty-five"));
+        assert_eq!(map.len(), 5);
+        assert_eq!(*map.get(&65).unwrap().value(), "LXV");
+    }
+
+    #[test]
+    fn test_get() {
+        let map = setup_map();
+        let entry = map.get(&70).unwrap();
+        assert_eq!(*entry.key(), 70);
+        assert_eq!(*entry.value(), "seventy");
+        assert!(map.get(&71).is_none());
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut map = setup_map();
+        *map.get_mut(&30).unwrap().value_mut() = "XXX";
+        assert_eq!(*map.get(&30).unwrap().value(), "XXX");
+        assert!(map.get_mut(&31).is_none());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut map = setup_map();
+        assert_eq!(map.remove(&50), Some("fifty"));
+        assert_eq!(map.remove(&50), None);
+        assert_eq!(map.len(), 4);
+        assert!(!map.contains_key(&50));
+        for key in [30, 65, 68, 70] {
+            assert!(map.contains_key(&key));
+        }
+    }
+
+    #[test]
+    fn test_remove_all() {
+        let mut map = setup_map();
+        for key in [68, 30, 70, 50, 65] {
+            assert!(map.remove(&key).is_some());
+        }
+        assert!(map.is_empty());
+        assert!(map.first_key_value().is_none());
+    }
+
+    #[test]
+    fn test_first_and_last_key_value() {
+        let map = setup_map();
+        let first = map.first_key_value().unwrap();
+        let last = map.last_key_value().unwrap();
+        assert_eq!((*first.key(), *first.value()), (30, "thirty"));
+        assert_eq!((*last.key(), *last.value()), (70, "seventy"));
+    }
+
+    #[test]
+    fn test_map_with_comparator() {
+        let mut map = AvlMap::with_comparator(ReverseOrder);
+        for key in 1..=10 {
+            map.insert(key, key * 10);
+        }
+        assert_eq!(*map.first_key_value().unwrap().key(), 10);
+        assert_eq!(*map.last_key_value().unwrap().key(), 1);
+        assert_eq!(map.remove(&4), Some(40));
+        assert_eq!(map.len(), 9);
+    }
+
+    #[test]
+    fn test_entry_counting() {
+        let mut map = AvlMap::new();
+        for word in "the cat and the hat and the bat".split(' ') {
+            map.entry(word).and_modify(|count| *count += 1).or_insert(1);
+        }
+        assert_eq!(map.len(), 5);
+        assert_eq!(*map.get(&"the").unwrap().value(), 3);
+        assert_eq!(*map.get(&"and").unwrap().value(), 2);
+        assert_eq!(*map.get(&"cat").unwrap().value(), 1);
+    }
+
+    #[test]
+    fn test_iter_and_range() {
+        let map = setup_map();
+        let keys: Vec<isize> = map.iter().map(|entry| *entry.key()).collect();
+        assert_eq!(keys, vec![30, 50, 65, 68, 70]);
+        assert_eq!(map.iter().len(), 5);
+        let values: Vec<&str> = map
+            .range(60..69)
+            .rev()
+            .map(|entry| *entry.value())
+            .collect();
+        assert_eq!(values, vec!["sixty-eight", "sixty-five"]);
+        assert!(map.range(71..).next().is_none());
+    }
+
+    #[test]
+    fn test_entry_or_insert_get_mut() {
+        let mut map = setup_map();
+        *map.entry(40).or_insert("forty").get_mut() = "XL";
+        assert_eq!(map.len(), 6);
+        assert_eq!(*map.get(&40).unwrap().value(), "XL");
+    }
+
+    #[test]
+    fn test_entry_remove() {
+        let mut map = setup_map();
+        match map.entry(68) {
+            Entry::Occupied(entry) => assert_eq!(entry.remove_entry(), (68, "sixty-eight")),
+            Entry::Vacant(_) => panic!("68 is in the map"),
+        }
+        assert_eq!(map.len(), 4);
+        assert!(!map.contains_key(&68));
+    }
+
+    #[test]
+    fn test_entry_vacant_key() {
+        let mut map = setup_map();
+        match map.entry(69) {
+            Entry::Occupied(_) => panic!("69 is not in the map"),
+            Entry::Vacant(entry) => assert_eq!(*entry.key(), 69),
+        }
+        assert_eq!(map.len(), 5);
+    }
+}
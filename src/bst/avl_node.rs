@@ -1,22 +1,39 @@
+use crate::bst::summary::Summary;
 use std::cell::RefCell;
 use std::cmp::max;
 use std::rc::{Rc, Weak};
 
 #[derive(Debug)]
-pub struct Node {
-    pub parent: WeakNodePtr,
-    pub index: isize,
-    pub value: isize,
+pub struct Node<K, V, S = ()> {
+    pub parent: WeakNodePtr<K, V, S>,
+    pub index: V,
+    pub value: K,
     pub height: isize,
-    pub left_child: Option<NodePtr>,
-    pub right_child: Option<NodePtr>,
+    // How many equal entries the node stands for under DuplicatePolicy::Count
+    pub count: usize,
+    // Entries in the subtree rooted here, counts included, kept up to date by set_height
+    pub size: usize,
+    // The tree's Summary of the subtree rooted here, refreshed by set_height along with size
+    pub summary: S,
+    // The Summary of the node's own entry, only rebuilt when its index or count changes so
+    // set_height just combines stored summaries
+    pub own_summary: S,
+    pub left_child: Option<NodePtr<K, V, S>>,
+    pub right_child: Option<NodePtr<K, V, S>>,
+    // Set once the node has been removed from its tree, so a handle to it reads as stale
+    pub detached: bool,
 }
 
-pub type NodePtr = Rc<RefCell<Node>>;
-pub type WeakNodePtr = Weak<RefCell<Node>>;
+pub type NodePtr<K, V, S = ()> = Rc<RefCell<Node<K, V, S>>>;
+pub type WeakNodePtr<K, V, S = ()> = Weak<RefCell<Node<K, V, S>>>;
 
-impl PartialEq for Node {
-    fn eq(&self, other: &Node) -> bool {
+// The node type the tree was originally written for, keyed and indexed by isize
+pub type IsizeNode = Node<isize, isize>;
+#[allow(dead_code)]
+pub type IsizeNodePtr = NodePtr<isize, isize>;
+
+impl<K: PartialEq, V: PartialEq, S> PartialEq for Node<K, V, S> {
+    fn eq(&self, other: &Node<K, V, S>) -> bool {
         self.index == other.index
             && self.value == other.value
             && self.left_child == other.left_child
@@ -24,26 +41,78 @@ impl PartialEq for Node {
     }
 }
 
-impl Node {
-    pub fn new(index: isize, value: isize) -> Node {
+impl<K, V, S: Summary<K, V>> Node<K, V, S> {
+    pub fn new(index: V, value: K) -> Node<K, V, S> {
+        let own_summary = S::from_entry(&index, &value, 1);
         Node {
             parent: Weak::new(),
+            summary: own_summary.clone(),
+            own_summary,
             index,
             value,
             height: 1,
+            count: 1,
+            size: 1,
             left_child: None,
             right_child: None,
+            detached: false,
         }
     }
 
-    pub fn same_structure_to(&self, node_ptr: &NodePtr) -> bool {
-        let same_index_value =
-            self.index == node_ptr.borrow().index && self.value == node_ptr.borrow().value;
-        let same_children_structure = self.get_left_child().is_none()
-            == node_ptr.borrow().get_left_child().is_none()
-            && self.get_right_child().is_none() == node_ptr.borrow().get_right_child().is_none();
-        let same_parent_structure = self.parent.upgrade().is_none() == node_ptr.borrow().parent.upgrade().is_none();
-        same_index_value && same_children_structure && same_parent_structure
+    // Also refreshes size and summary, which depend on the children the same way height does
+    pub fn set_height(&mut self) {
+        self.height = max(self.get_right_child_height(), self.get_left_child_height()) + 1;
+        self.size = self.get_left_child_size() + self.count + self.get_right_child_size();
+        let left_and_own_summary = match &self.left_child {
+            Some(refcell) => S::combine(&refcell.borrow().summary, &self.own_summary),
+            None => self.own_summary.clone(),
+        };
+        self.summary = match &self.right_child {
+            Some(refcell) => S::combine(&left_and_own_summary, &refcell.borrow().summary),
+            None => left_and_own_summary,
+        };
+    }
+
+    // Rebuilds the own summary of node_ptr and runs set_height on it and every ancestor, for
+    // when its index or count changed in place
+    pub(crate) fn refresh_upwards(node_ptr: NodePtr<K, V, S>) {
+        node_ptr.borrow_mut().refresh_own_summary();
+        let mut current_node_ptr_option = Some(node_ptr);
+        while let Some(current_node_ptr) = current_node_ptr_option {
+            current_node_ptr.borrow_mut().set_height();
+            current_node_ptr_option = current_node_ptr.borrow().parent.upgrade();
+        }
+    }
+
+    pub fn refresh_own_summary(&mut self) {
+        self.own_summary = S::from_entry(&self.index, &self.value, self.count);
+    }
+
+    pub fn get_own_summary(&self) -> &S {
+        &self.own_summary
+    }
+
+    pub fn get_left_child_summary(&self) -> S {
+        match &self.left_child {
+            Some(refcell) => refcell.borrow().summary.clone(),
+            None => S::identity(),
+        }
+    }
+
+    pub fn get_right_child_summary(&self) -> S {
+        match &self.right_child {
+            Some(refcell) => refcell.borrow().summary.clone(),
+            None => S::identity(),
+        }
+    }
+}
+
+impl<K, V, S> Node<K, V, S> {
+    // Moves the payload out of a node already unlinked from its tree, handing the node back
+    // when another handle still shares it
+    pub(crate) fn try_into_payload(node_ptr: NodePtr<K, V, S>) -> Result<(V, K), NodePtr<K, V, S>> {
+        let node = Rc::try_unwrap(node_ptr)?.into_inner();
+        Ok((node.index, node.value))
     }
 
     fn get_left_child_height(&self) -> isize {
@@ -64,35 +133,40 @@ impl Node {
         self.get_right_child_height() - self.get_left_child_height()
     }
 
-    pub fn set_height(&mut self) {
-        self.height = max(self.get_right_child_height(), self.get_left_child_height()) + 1;
-    }
-
-    pub fn get_value(&self) -> isize {
-        self.value
-    }
-
-    pub fn get_left_child(&self) -> Option<NodePtr> {
+    pub fn get_left_child_size(&self) -> usize {
         match &self.left_child {
-            Some(node_ptr) => Some(Rc::clone(node_ptr)),
-            None => None,
+            Some(refcell) => refcell.borrow().size,
+            None => 0,
         }
     }
 
-    pub fn get_right_child(&self) -> Option<NodePtr> {
+    pub fn get_right_child_size(&self) -> usize {
         match &self.right_child {
-            Some(node_ptr) => Some(Rc::clone(node_ptr)),
-            None => None,
+            Some(refcell) => refcell.borrow().size,
+            None => 0,
         }
     }
 
+    pub fn get_value(&self) -> &K {
+        &self.value
+    }
+
+    pub fn get_left_child(&self) -> Option<NodePtr<K, V, S>> {
+        self.left_child.as_ref().map(Rc::clone)
+    }
+
+    pub fn get_right_child(&self) -> Option<NodePtr<K, V, S>> {
+        self.right_child.as_ref().map(Rc::clone)
+    }
+
+    // Compares node identity rather than contents, so equal payloads are never confused
     pub fn is_left_child(&self) -> bool {
         let parent_ptr_option = self.parent.upgrade();
         match parent_ptr_option {
             Some(parent_ptr) => {
                 let left_child_ptr_option = parent_ptr.borrow().get_left_child();
                 match left_child_ptr_option {
-                    Some(left_child_ptr) => left_child_ptr.borrow().eq(self),
+                    Some(left_child_ptr) => std::ptr::eq(left_child_ptr.as_ptr(), self),
                     None => false,
                 }
             }
@@ -4,30 +4,30 @@ mod tests {
     use std::cell::RefCell;
     use std::rc::Rc;
 
-    fn setup_node1() -> Node {
-        Node::new(10, 20)
+    fn setup_node1() -> IsizeNode {
+        IsizeNode::new(10, 20)
     }
 
-    fn setup_node2() -> Node {
-        let left_node = Rc::new(RefCell::new(Node::new(5, 10)));
+    fn setup_node2() -> IsizeNode {
+        let left_node = Rc::new(RefCell::new(IsizeNode::new(5, 10)));
         let right_node = Rc::new(RefCell::new(setup_node1()));
-        let mut my_node = Node::new(1, 15);
+        let mut my_node = IsizeNode::new(1, 15);
         my_node.left_child = Some(Rc::clone(&left_node));
         my_node.right_child = Some(Rc::clone(&right_node));
         my_node
     }
 
-    fn setup_node3() -> Node {
+    fn setup_node3() -> IsizeNode {
         let right_node = Rc::new(RefCell::new(setup_node1()));
-        let mut my_node = Node::new(1, 15);
+        let mut my_node = IsizeNode::new(1, 15);
         my_node.right_child = Some(Rc::clone(&right_node));
         my_node
     }
 
-    fn setup_node4() -> NodePtr {
-        let left_node = Rc::new(RefCell::new(Node::new(5, 10)));
+    fn setup_node4() -> IsizeNodePtr {
+        let left_node = Rc::new(RefCell::new(IsizeNode::new(5, 10)));
         let right_node = Rc::new(RefCell::new(setup_node1()));
-        let mut my_node = Node::new(1, 15);
+        let mut my_node = IsizeNode::new(1, 15);
         my_node.left_child = Some(Rc::clone(&left_node));
         my_node.right_child = Some(Rc::clone(&right_node));
         let my_new_node = Rc::new(RefCell::new(my_node));
@@ -78,6 +78,17 @@ mod tests {
         assert_eq!(node.height, 2);
     }
 
+    #[test]
+    fn test_node2_size() {
+        let mut node = setup_node2();
+        assert_eq!(node.size, 1);
+        node.set_height();
+        assert_eq!(node.size, 3);
+        node.count = 2;
+        node.set_height();
+        assert_eq!(node.size, 4);
+    }
+
     #[test]
     fn test_node2_balance_factor() {
         let node = setup_node2();
@@ -100,24 +111,38 @@ mod tests {
     #[test]
     fn test_node3_has_both_children() {
         let node = setup_node3();
-        assert_eq!(node.has_both_children(), false);
+        assert!(!node.has_both_children());
     }
 
     #[test]
     fn test_node4_has_both_children() {
         let node = setup_node4();
-        assert_eq!(node.borrow().has_both_children(), true);
+        assert!(node.borrow().has_both_children());
     }
 
     #[test]
     fn test_node3_has_only_left_child() {
         let node = setup_node3();
-        assert_eq!(node.has_only_left_child(), false);
+        assert!(!node.has_only_left_child());
     }
 
     #[test]
     fn test_node3_has_only_right_child() {
         let node = setup_node3();
-        assert_eq!(node.has_only_right_child(), true);
+        assert!(node.has_only_right_child());
+    }
+
+    #[test]
+    fn test_equal_siblings_are_told_apart() {
+        let parent = Rc::new(RefCell::new(IsizeNode::new(0, 5)));
+        let left_node = Rc::new(RefCell::new(IsizeNode::new(0, 5)));
+        let right_node = Rc::new(RefCell::new(IsizeNode::new(0, 5)));
+        left_node.borrow_mut().parent = Rc::downgrade(&parent);
+        right_node.borrow_mut().parent = Rc::downgrade(&parent);
+        parent.borrow_mut().left_child = Some(Rc::clone(&left_node));
+        parent.borrow_mut().right_child = Some(Rc::clone(&right_node));
+        assert_eq!(*left_node.borrow(), *right_node.borrow());
+        assert!(left_node.borrow().is_left_child());
+        assert!(right_node.borrow().is_right_child());
     }
 }
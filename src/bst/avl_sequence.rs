@@ -0,0 +1,206 @@
+use crate::bst::avl_node::{Node, NodePtr};
+use crate::bst::avl_tree::{self, AvlTree};
+use crate::bst::comparator::NaturalOrder;
+use crate::bst::summary::Summary;
+use std::rc::Rc;
+
+// Positional list over AvlTree: every element is a node index under the same empty value, so
+// the comparator never tells nodes apart and an element's position comes only from the subtree
+// sizes on its way to the root. Inserting, removing and looking up a position are O(log n),
+// and so are splitting and concatenating, which cut and join trees instead of moving elements:
+// a join only rebalances the taller side below its root, see AvlTree::join_subtrees
+#[derive(Debug)]
+pub struct AvlSequence<T, S = ()> {
+    tree: AvlTree<(), T, NaturalOrder, S>,
+}
+
+// Walks the elements of an AvlSequence in order, from either end
+pub struct Iter<'a, T, S = ()> {
+    inner: avl_tree::Iter<'a, (), T, S>,
+}
+
+impl<T, S: Summary<(), T>> Default for AvlSequence<T, S> {
+    fn default() -> Self {
+        AvlSequence::new()
+    }
+}
+
+impl<T, S: Summary<(), T>> AvlSequence<T, S> {
+    pub fn new() -> AvlSequence<T, S> {
+        AvlSequence {
+            tree: AvlTree::summarized(NaturalOrder),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    pub fn height(&self) -> isize {
+        self.tree.height()
+    }
+
+    // Combined summary of every element, in order
+    pub fn summary(&self) -> S {
+        match &self.tree.root {
+            Some(root_ptr) => root_ptr.borrow().summary.clone(),
+            None => S::identity(),
+        }
+    }
+
+    pub fn get(&self, position: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        let node_ptr = self.tree.select(position)?;
+        let element = node_ptr.borrow().index.clone();
+        Some(element)
+    }
+
+    // Finds the element where the running total of measure, taken over the summaries in
+    // order, first goes past target. Returns its position together with the combined summary
+    // of every element before it, or None when the whole sequence never gets past target.
+    // measure must add up over combine, like a length or a count
+    pub fn seek_by<F: Fn(&S) -> usize>(&self, target: usize, measure: F) -> Option<(usize, S)> {
+        let mut position = 0;
+        let mut summary_before = S::identity();
+        let mut current_node_ptr_option = self.tree.root.clone();
+        while let Some(current_node_ptr) = current_node_ptr_option {
+            let node = current_node_ptr.borrow();
+            let left_summary = S::combine(&summary_before, &node.get_left_child_summary());
+            let own_summary = node.get_own_summary();
+            current_node_ptr_option = if target < measure(&left_summary) {
+                node.get_left_child()
+            } else if target < measure(&left_summary) + measure(own_summary) {
+                return Some((position + node.get_left_child_size(), left_summary));
+            } else {
+                position += node.get_left_child_size() + node.count;
+                summary_before = S::combine(&left_summary, own_summary);
+                node.get_right_child()
+            };
+        }
+        None
+    }
+
+    // Replaces the element at position, returning the old one, or None past the end
+    pub fn set(&mut self, position: usize, element: T) -> Option<T> {
+        let node_ptr = self.tree.select(position)?;
+        let old_element = std::mem::replace(&mut node_ptr.borrow_mut().index, element);
+        self.tree.refresh_node(&node_ptr);
+        Some(old_element)
+    }
+
+    // Inserts element so that it ends up at position, shifting the later elements back.
+    // Panics when position is greater than the length, like Vec::insert
+    pub fn insert_at(&mut self, position: usize, element: T) {
+        assert!(
+            position <= self.len(),
+            "insertion position {} is past the end of a sequence of length {}",
+            position,
+            self.len()
+        );
+        let node = Node::new(element, ());
+        let leaf_position = match self.tree.select(position) {
+            Some(node_ptr) => match node_ptr.borrow().get_left_child() {
+                Some(left_child_ptr) => Some((Self::rightmost(left_child_ptr), false)),
+                None => Some((Rc::clone(&node_ptr), true)),
+            },
+            None => self
+                .tree
+                .root
+                .clone()
+                .map(|root_ptr| (Self::rightmost(root_ptr), false)),
+        };
+        self.tree.attach_node(leaf_position, node);
+    }
+
+    pub fn push_front(&mut self, element: T) {
+        self.insert_at(0, element);
+    }
+
+    pub fn push_back(&mut self, element: T) {
+        self.insert_at(self.len(), element);
+    }
+
+    // Removes and returns the element at position, shifting the later elements forward
+    pub fn remove_at(&mut self, position: usize) -> Option<T> {
+        let node_ptr = self.tree.select(position)?;
+        let (element, _) = self.tree.take_entry(node_ptr).ok()?;
+        Some(element)
+    }
+
+    // Splits into the elements before position and the rest. Panics when position is greater
+    // than the length
+    pub fn split_at(mut self, position: usize) -> (AvlSequence<T, S>, AvlSequence<T, S>) {
+        assert!(
+            position <= self.len(),
+            "split position {} is past the end of a sequence of length {}",
+            position,
+            self.len()
+        );
+        let tail = self.tree.split_off_at(position);
+        (self, AvlSequence { tree: tail })
+    }
+
+    // The elements of self followed by those of other
+    pub fn concat(mut self, mut other: AvlSequence<T, S>) -> AvlSequence<T, S> {
+        self.tree.append_tree(&mut other.tree);
+        self
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, S> {
+        Iter {
+            inner: self.tree.iter(),
+        }
+    }
+
+    // The elements in order, lent through EntryRef instead of cloned, for the facades built on
+    // a sequence
+    pub(crate) fn entries(&self) -> avl_tree::Iter<'_, (), T, S> {
+        self.tree.iter()
+    }
+
+    fn rightmost(node_ptr: NodePtr<(), T, S>) -> NodePtr<(), T, S> {
+        AvlTree::<(), T, NaturalOrder, S>::get_maximum_child(node_ptr)
+    }
+}
+
+impl<T, S: Summary<(), T>> FromIterator<T> for AvlSequence<T, S> {
+    fn from_iter<I: IntoIterator<Item = T>>(elements: I) -> Self {
+        let mut sequence = AvlSequence::new();
+        sequence.extend(elements);
+        sequence
+    }
+}
+
+impl<T, S: Summary<(), T>> Extend<T> for AvlSequence<T, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, elements: I) {
+        for element in elements {
+            self.push_back(element);
+        }
+    }
+}
+
+impl<'a, T: Clone, S> Iterator for Iter<'a, T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next().map(|entry| entry.index().clone())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T: Clone, S> DoubleEndedIterator for Iter<'a, T, S> {
+    fn next_back(&mut self) -> Option<T> {
+        self.inner.next_back().map(|entry| entry.index().clone())
+    }
+}
+
+impl<'a, T: Clone, S> ExactSizeIterator for Iter<'a, T, S> {}
@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod tests {
+    use super::super::avl_sequence::*;
+    use super::super::summary::IndexSum;
+
+    type SumSequence = AvlSequence<isize, IndexSum<isize>>;
+
+    // Small deterministic generator so the operation mix is the same on every run
+    fn next_random(state: &mut u64) -> usize {
+        *state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (*state >> 33) as usize
+    }
+
+    fn assert_matches(sequence: &SumSequence, model: &[isize]) {
+        assert_eq!(sequence.len(), model.len());
+        assert_eq!(sequence.iter().collect::<Vec<_>>(), model);
+        assert_eq!(sequence.summary(), IndexSum(model.iter().sum()));
+        // The AVL height bound, which a badly joined tree quickly breaks
+        let bound = 1.4405 * ((model.len() + 2) as f64).log2() - 0.3277;
+        assert!(sequence.height() as f64 <= bound);
+    }
+
+    #[test]
+    fn test_insert_and_get_at_positions() {
+        let mut sequence: AvlSequence<char> = AvlSequence::new();
+        sequence.push_back('b');
+        sequence.push_front('a');
+        sequence.push_back('d');
+        sequence.insert_at(2, 'c');
+        sequence.insert_at(4, 'e');
+        assert_eq!(sequence.iter().collect::<String>(), "abcde");
+        assert_eq!(sequence.get(3), Some('d'));
+        assert_eq!(sequence.get(5), None);
+        assert_eq!(sequence.iter().rev().collect::<String>(), "edcba");
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion position 2 is past the end")]
+    fn test_insert_past_the_end_panics() {
+        let mut sequence: AvlSequence<isize> = AvlSequence::new();
+        sequence.push_back(1);
+        sequence.insert_at(2, 5);
+    }
+
+    #[test]
+    fn test_random_edits_match_a_vec() {
+        let mut state = 7;
+        let mut sequence = SumSequence::new();
+        let mut model = Vec::new();
+        for step in 0..2000 {
+            let choice = next_random(&mut state) % 10;
+            if choice < 6 || model.is_empty() {
+                let position = next_random(&mut state) % (model.len() + 1);
+                sequence.insert_at(position, step);
+                model.insert(position, step);
+            } else if choice < 9 {
+                let position = next_random(&mut state) % model.len();
+                assert_eq!(sequence.remove_at(position), Some(model.remove(position)));
+            } else {
+                let position = next_random(&mut state) % model.len();
+                assert_eq!(sequence.set(position, -step), Some(model[position]));
+                model[position] = -step;
+            }
+            if step % 100 == 0 {
+                assert_matches(&sequence, &model);
+                let position = next_random(&mut state) % (model.len() + 1);
+                assert_eq!(sequence.get(position), model.get(position).copied());
+            }
+        }
+        assert_matches(&sequence, &model);
+        assert_eq!(sequence.remove_at(model.len()), None);
+    }
+
+    #[test]
+    fn test_split_at_every_position() {
+        let model: Vec<isize> = (0..40).collect();
+        for position in 0..=model.len() {
+            let sequence: SumSequence = model.iter().copied().collect();
+            let (front, back) = sequence.split_at(position);
+            assert_matches(&front, &model[..position]);
+            assert_matches(&back, &model[position..]);
+        }
+    }
+
+    #[test]
+    fn test_concat_sequences_of_very_different_lengths() {
+        for (front_len, back_len) in [(0, 5), (5, 0), (1, 300), (300, 1), (17, 200), (64, 64)] {
+            let front: SumSequence = (0..front_len).collect();
+            let back: SumSequence = (front_len..front_len + back_len).collect();
+            let mut joined = front.concat(back);
+            let model: Vec<isize> = (0..front_len + back_len).collect();
+            assert_matches(&joined, &model);
+            // The joined tree must stay balanced enough to keep taking edits
+            joined.insert_at(model.len() / 2, -1);
+            joined.remove_at(model.len() / 2);
+            assert_matches(&joined, &model);
+        }
+    }
+
+    #[test]
+    fn test_split_and_concat_round_trips() {
+        let mut state = 11;
+        let mut model: Vec<isize> = (0..500).collect();
+        let mut sequence: SumSequence = model.iter().copied().collect();
+        for _ in 0..50 {
+            let first_cut = next_random(&mut state) % (model.len() + 1);
+            let second_cut = first_cut + next_random(&mut state) % (model.len() - first_cut + 1);
+            // Move the middle piece to the front
+            let (front, rest) = sequence.split_at(first_cut);
+            let (middle, back) = rest.split_at(second_cut - first_cut);
+            sequence = middle.concat(front).concat(back);
+            model[..second_cut].rotate_left(first_cut);
+            assert_matches(&sequence, &model);
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use crate::bst::avl_entry::Entry;
+use crate::bst::avl_node::NodePtr;
+use crate::bst::avl_tree::{self, AvlTree};
+use crate::bst::comparator::{Comparator, NaturalOrder};
+use std::cell::Ref;
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::marker::PhantomData;
+
+// Set facade over AvlTree: every element is a node value with an empty index
+#[derive(Debug)]
+pub struct AvlSet<T, C = NaturalOrder> {
+    tree: AvlTree<T, (), C>,
+}
+
+// Read access to one element of an AvlSet, borrowed from its node instead of copied out
+pub struct SetRef<'a, T> {
+    node_ptr: NodePtr<T, ()>,
+    marker: PhantomData<&'a ()>,
+}
+
+// Walks the elements of an AvlSet in ascending order, from either end
+pub struct Iter<'a, T> {
+    inner: avl_tree::Iter<'a, T, ()>,
+}
+
+// Elements in either set, each yielded once
+pub struct Union<'a, T, C> {
+    left: Peekable<Iter<'a, T>>,
+    right: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+// Elements in both sets
+pub struct Intersection<'a, T, C> {
+    left: Peekable<Iter<'a, T>>,
+    right: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+// Elements in the left set but not in the right one
+pub struct Difference<'a, T, C> {
+    left: Peekable<Iter<'a, T>>,
+    right: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+// Elements in exactly one of the two sets
+pub struct SymmetricDifference<'a, T, C> {
+    left: Peekable<Iter<'a, T>>,
+    right: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+impl<'a, T> SetRef<'a, T> {
+    fn new(node_ptr: NodePtr<T, ()>) -> SetRef<'a, T> {
+        SetRef {
+            node_ptr,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn value(&self) -> Ref<'_, T> {
+        Ref::map(self.node_ptr.borrow(), |node| &node.value)
+    }
+}
+
+impl<T, C: Default> Default for AvlSet<T, C> {
+    fn default() -> Self {
+        AvlSet::with_comparator(C::default())
+    }
+}
+
+impl<T: Ord> AvlSet<T> {
+    pub fn new() -> AvlSet<T> {
+        AvlSet::with_comparator(NaturalOrder)
+    }
+}
+
+impl<T, C> AvlSet<T, C> {
+    pub fn with_comparator(comparator: C) -> AvlSet<T, C> {
+        AvlSet {
+            tree: AvlTree::with_comparator(comparator),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.tree.iter(),
+        }
+    }
+}
+
+impl<T, C: Comparator<T>> AvlSet<T, C> {
+    // Returns false when the set already holds an equal element
+    pub fn insert(&mut self, value: T) -> bool {
+        match self.tree.entry(value) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(());
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, value: &T) -> bool {
+        match self.tree.search_tree_by_value(value) {
+            Some(node_ptr) => {
+                self.tree.unlink_node(node_ptr);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.tree.search_tree_by_value(value).is_some()
+    }
+}
+
+impl<T, C: Comparator<T>> AvlSet<T, C> {
+    pub fn is_subset(&self, other: &AvlSet<T, C>) -> bool {
+        self.len() <= other.len() && self.iter().all(|element| other.contains(&element.value()))
+    }
+
+    pub fn is_superset(&self, other: &AvlSet<T, C>) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &AvlSet<T, C>) -> bool {
+        self.intersection(other).next().is_none()
+    }
+
+    pub fn union<'a>(&'a self, other: &'a AvlSet<T, C>) -> Union<'a, T, C> {
+        Union {
+            left: self.iter().peekable(),
+            right: other.iter().peekable(),
+            comparator: self.tree.comparator(),
+        }
+    }
+
+    pub fn intersection<'a>(&'a self, other: &'a AvlSet<T, C>) -> Intersection<'a, T, C> {
+        Intersection {
+            left: self.iter().peekable(),
+            right: other.iter().peekable(),
+            comparator: self.tree.comparator(),
+        }
+    }
+
+    pub fn difference<'a>(&'a self, other: &'a AvlSet<T, C>) -> Difference<'a, T, C> {
+        Difference {
+            left: self.iter().peekable(),
+            right: other.iter().peekable(),
+            comparator: self.tree.comparator(),
+        }
+    }
+
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a AvlSet<T, C>,
+    ) -> SymmetricDifference<'a, T, C> {
+        SymmetricDifference {
+            left: self.iter().peekable(),
+            right: other.iter().peekable(),
+            comparator: self.tree.comparator(),
+        }
+    }
+}
+
+// Orders the heads of two sorted streams; a missing head sorts after everything
+fn compare_heads<T, C: Comparator<T>>(
+    left: Option<&SetRef<'_, T>>,
+    right: Option<&SetRef<'_, T>>,
+    comparator: &C,
+) -> Option<Ordering> {
+    match (left, right) {
+        (None, None) => None,
+        (Some(_), None) => Some(Ordering::Less),
+        (None, Some(_)) => Some(Ordering::Greater),
+        (Some(left_element), Some(right_element)) => {
+            Some(comparator.compare(&left_element.value(), &right_element.value()))
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = SetRef<'a, T>;
+
+    fn next(&mut self) -> Option<SetRef<'a, T>> {
+        self.inner
+            .next()
+            .map(|element| SetRef::new(element.into_node_ptr()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<SetRef<'a, T>> {
+        self.inner
+            .next_back()
+            .map(|element| SetRef::new(element.into_node_ptr()))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T, C: Comparator<T>> Iterator for Union<'a, T, C> {
+    type Item = SetRef<'a, T>;
+
+    fn next(&mut self) -> Option<SetRef<'a, T>> {
+        match compare_heads(self.left.peek(), self.right.peek(), self.comparator)? {
+            Ordering::Less => self.left.next(),
+            Ordering::Greater => self.right.next(),
+            Ordering::Equal => {
+                self.right.next();
+                self.left.next()
+            }
+        }
+    }
+}
+
+impl<'a, T, C: Comparator<T>> Iterator for Intersection<'a, T, C> {
+    type Item = SetRef<'a, T>;
+
+    fn next(&mut self) -> Option<SetRef<'a, T>> {
+        loop {
+            let left_element = self.left.peek()?;
+            let right_element = self.right.peek()?;
+            let ordering = self
+                .comparator
+                .compare(&left_element.value(), &right_element.value());
+            match ordering {
+                Ordering::Less => {
+                    self.left.next();
+                }
+                Ordering::Greater => {
+                    self.right.next();
+                }
+                Ordering::Equal => {
+                    self.right.next();
+                    return self.left.next();
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T, C: Comparator<T>> Iterator for Difference<'a, T, C> {
+    type Item = SetRef<'a, T>;
+
+    fn next(&mut self) -> Option<SetRef<'a, T>> {
+        loop {
+            let ordering = match (self.left.peek(), self.right.peek()) {
+                (None, _) => return None,
+                (Some(_), None) => Ordering::Less,
+                (Some(left_element), Some(right_element)) => self
+                    .comparator
+                    .compare(&left_element.value(), &right_element.value()),
+            };
+            match ordering {
+                Ordering::Less => return self.left.next(),
+                Ordering::Greater => {
+                    self.right.next();
+                }
+                Ordering::Equal => {
+                    self.left.next();
+                    self.right.next();
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T, C: Comparator<T>> Iterator for SymmetricDifference<'a, T, C> {
+    type Item = SetRef<'a, T>;
+
+    fn next(&mut self) -> Option<SetRef<'a, T>> {
+        loop {
+            match compare_heads(self.left.peek(), self.right.peek(), self.comparator)? {
+                Ordering::Less => return self.left.next(),
+                Ordering::Greater => return self.right.next(),
+                Ordering::Equal => {
+                    self.left.next();
+                    self.right.next();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod tests {
+    use super::super::avl_set::*;
+    use super::super::comparator::ReverseOrder;
+
+    // Copies the elements met by a set iterator
+    fn values<'a, I: Iterator<Item = SetRef<'a, isize>>>(elements: I) -> Vec<isize> {
+        elements.map(|element| *element.value()).collect()
+    }
+
+    fn setup_set(values: &[isize]) -> AvlSet<isize> {
+        let mut set = AvlSet::new();
+        for value in values {
+            set.insert(*value);
+        }
+        set
+    }
+
+    #[test]
+    fn test_insert_and_contains() {
+        let mut set = AvlSet::new();
+        assert!(set.insert(5));
+        assert!(set.insert(3));
+        assert!(!set.insert(5));
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&3));
+        assert!(!set.contains(&4));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set = setup_set(&[30, 50, 70, 65, 68]);
+        assert!(set.remove(&50));
+        assert!(!set.remove(&50));
+        assert_eq!(set.len(), 4);
+        assert_eq!(values(set.iter()), vec![30, 65, 68, 70]);
+    }
+
+    #[test]
+    fn test_iter_sorted() {
+        let set = setup_set(&[8, 3, 10, 1, 6, 14, 4, 7, 13]);
+        assert_eq!(values(set.iter()), vec![1, 3, 4, 6, 7, 8, 10, 13, 14]);
+        assert!(AvlSet::<isize>::new().iter().next().is_none());
+    }
+
+    #[test]
+    fn test_iter_rev() {
+        let set = setup_set(&[8, 3, 10, 1, 6]);
+        assert_eq!(set.iter().len(), 5);
+        assert_eq!(values(set.iter().rev()), vec![10, 8, 6, 3, 1]);
+    }
+
+    #[test]
+    fn test_subset_and_superset() {
+        let small = setup_set(&[2, 4]);
+        let large = setup_set(&[1, 2, 3, 4]);
+        assert!(small.is_subset(&large));
+        assert!(!large.is_subset(&small));
+        assert!(large.is_superset(&small));
+        assert!(small.is_subset(&small));
+        assert!(AvlSet::new().is_subset(&small));
+    }
+
+    #[test]
+    fn test_is_disjoint() {
+        let evens = setup_set(&[2, 4, 6]);
+        let odds = setup_set(&[1, 3, 5]);
+        let mixed = setup_set(&[5, 6]);
+        assert!(evens.is_disjoint(&odds));
+        assert!(!evens.is_disjoint(&mixed));
+    }
+
+    #[test]
+    fn test_union() {
+        let left = setup_set(&[1, 3, 5, 7]);
+        let right = setup_set(&[2, 3, 4, 8]);
+        assert_eq!(values(left.union(&right)), vec![1, 2, 3, 4, 5, 7, 8]);
+    }
+
+    #[test]
+    fn test_intersection() {
+        let left = setup_set(&[1, 3, 5, 7]);
+        let right = setup_set(&[2, 3, 4, 7, 8]);
+        assert_eq!(values(left.intersection(&right)), vec![3, 7]);
+    }
+
+    #[test]
+    fn test_difference() {
+        let left = setup_set(&[1, 3, 5, 7]);
+        let right = setup_set(&[2, 3, 4, 7, 8]);
+        assert_eq!(values(left.difference(&right)), vec![1, 5]);
+        assert_eq!(values(right.difference(&left)), vec![2, 4, 8]);
+    }
+
+    #[test]
+    fn test_symmetric_difference() {
+        let left = setup_set(&[1, 3, 5, 7]);
+        let right = setup_set(&[2, 3, 4, 7, 8]);
+        assert_eq!(
+            values(left.symmetric_difference(&right)),
+            vec![1, 2, 4, 5, 8]
+        );
+    }
+
+    #[test]
+    fn test_set_algebra_is_lazy() {
+        let left = setup_set(&(0..1000).collect::<Vec<_>>());
+        let right = setup_set(&(500..1500).collect::<Vec<_>>());
+        let mut intersection = left.intersection(&right);
+        assert_eq!(
+            intersection.next().map(|element| *element.value()),
+            Some(500)
+        );
+        assert_eq!(
+            intersection.next().map(|element| *element.value()),
+            Some(501)
+        );
+    }
+
+    // An element ordered by its id that can't be cloned
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Ticket(usize);
+
+    #[test]
+    fn test_set_algebra_without_clone() {
+        let mut left = AvlSet::new();
+        let mut right = AvlSet::new();
+        for id in [1, 2, 3] {
+            left.insert(Ticket(id));
+        }
+        for id in [2, 3, 4] {
+            right.insert(Ticket(id));
+        }
+        let ids: Vec<usize> = left
+            .symmetric_difference(&right)
+            .map(|element| element.value().0)
+            .collect();
+        assert_eq!(ids, vec![1, 4]);
+        assert!(!left.is_subset(&right));
+    }
+
+    #[test]
+    fn test_set_with_comparator() {
+        let mut left = AvlSet::with_comparator(ReverseOrder);
+        let mut right = AvlSet::with_comparator(ReverseOrder);
+        for value in [1, 2, 3] {
+            left.insert(value);
+        }
+        for value in [2, 3, 4] {
+            right.insert(value);
+        }
+        assert_eq!(values(left.union(&right)), vec![4, 3, 2, 1]);
+        assert_eq!(values(left.difference(&right)), vec![1]);
+    }
+}
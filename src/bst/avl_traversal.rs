@@ -0,0 +1,149 @@
+use crate::bst::avl_node::NodePtr;
+use crate::bst::avl_tree::AvlTree;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+// Snapshot of one node met during a traversal; depth counts edges from the starting node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeView<K, V> {
+    pub index: V,
+    pub value: K,
+    pub height: isize,
+    pub balance_factor: isize,
+    pub depth: usize,
+}
+
+// Node first, then its left and right subtrees
+pub struct PreOrder<'a, K, V, S = ()> {
+    stack: Vec<(NodePtr<K, V, S>, usize)>,
+    marker: PhantomData<&'a ()>,
+}
+
+// Left and right subtrees first, then the node
+pub struct PostOrder<'a, K, V, S = ()> {
+    // The flag tells whether the node's children were already pushed
+    stack: Vec<(NodePtr<K, V, S>, usize, bool)>,
+    marker: PhantomData<&'a ()>,
+}
+
+// Breadth first, left to right on each level
+pub struct LevelOrder<'a, K, V, S = ()> {
+    queue: VecDeque<(NodePtr<K, V, S>, usize)>,
+    marker: PhantomData<&'a ()>,
+}
+
+impl<K: Clone, V: Clone> NodeView<K, V> {
+    fn new<S>(node_ptr: &NodePtr<K, V, S>, depth: usize) -> NodeView<K, V> {
+        let node = node_ptr.borrow();
+        NodeView {
+            index: node.index.clone(),
+            value: node.value.clone(),
+            height: node.height,
+            balance_factor: node.get_balance_factor(),
+            depth,
+        }
+    }
+}
+
+impl<K, V, C, S> AvlTree<K, V, C, S> {
+    pub fn pre_order(&self) -> PreOrder<'_, K, V, S> {
+        PreOrder::from_root(self.root.as_ref())
+    }
+
+    pub fn post_order(&self) -> PostOrder<'_, K, V, S> {
+        PostOrder::from_root(self.root.as_ref())
+    }
+
+    pub fn level_order(&self) -> LevelOrder<'_, K, V, S> {
+        LevelOrder::from_root(self.root.as_ref())
+    }
+}
+
+impl<'a, K, V, S> PreOrder<'a, K, V, S> {
+    pub fn from_root(root: Option<&'a NodePtr<K, V, S>>) -> PreOrder<'a, K, V, S> {
+        PreOrder {
+            stack: root
+                .map(|root_ptr| (Rc::clone(root_ptr), 0))
+                .into_iter()
+                .collect(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V, S> PostOrder<'a, K, V, S> {
+    pub fn from_root(root: Option<&'a NodePtr<K, V, S>>) -> PostOrder<'a, K, V, S> {
+        PostOrder {
+            stack: root
+                .map(|root_ptr| (Rc::clone(root_ptr), 0, false))
+                .into_iter()
+                .collect(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V, S> LevelOrder<'a, K, V, S> {
+    pub fn from_root(root: Option<&'a NodePtr<K, V, S>>) -> LevelOrder<'a, K, V, S> {
+        LevelOrder {
+            queue: root
+                .map(|root_ptr| (Rc::clone(root_ptr), 0))
+                .into_iter()
+                .collect(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, K: Clone, V: Clone, S> Iterator for PreOrder<'a, K, V, S> {
+    type Item = NodeView<K, V>;
+
+    fn next(&mut self) -> Option<NodeView<K, V>> {
+        let (node_ptr, depth) = self.stack.pop()?;
+        if let Some(right_child_ptr) = node_ptr.borrow().get_right_child() {
+            self.stack.push((right_child_ptr, depth + 1));
+        }
+        if let Some(left_child_ptr) = node_ptr.borrow().get_left_child() {
+            self.stack.push((left_child_ptr, depth + 1));
+        }
+        Some(NodeView::new(&node_ptr, depth))
+    }
+}
+
+impl<'a, K: Clone, V: Clone, S> Iterator for PostOrder<'a, K, V, S> {
+    type Item = NodeView<K, V>;
+
+    fn next(&mut self) -> Option<NodeView<K, V>> {
+        loop {
+            let (node_ptr, depth, children_pushed) = self.stack.pop()?;
+            if children_pushed {
+                return Some(NodeView::new(&node_ptr, depth));
+            }
+            let left_child = node_ptr.borrow().get_left_child();
+            let right_child = node_ptr.borrow().get_right_child();
+            self.stack.push((node_ptr, depth, true));
+            if let Some(right_child_ptr) = right_child {
+                self.stack.push((right_child_ptr, depth + 1, false));
+            }
+            if let Some(left_child_ptr) = left_child {
+                self.stack.push((left_child_ptr, depth + 1, false));
+            }
+        }
+    }
+}
+
+impl<'a, K: Clone, V: Clone, S> Iterator for LevelOrder<'a, K, V, S> {
+    type Item = NodeView<K, V>;
+
+    fn next(&mut self) -> Option<NodeView<K, V>> {
+        let (node_ptr, depth) = self.queue.pop_front()?;
+        if let Some(left_child_ptr) = node_ptr.borrow().get_left_child() {
+            self.queue.push_back((left_child_ptr, depth + 1));
+        }
+        if let Some(right_child_ptr) = node_ptr.borrow().get_right_child() {
+            self.queue.push_back((right_child_ptr, depth + 1));
+        }
+        Some(NodeView::new(&node_ptr, depth))
+    }
+}
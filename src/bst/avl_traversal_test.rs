@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use super::super::avl_node::*;
+    use super::super::avl_traversal::*;
+    use super::super::avl_tree::*;
+
+    //        20
+    //      /    \
+    //    10      30
+    //   /  \       \
+    //  5    15      40
+    fn setup_tree() -> IsizeAvlTree {
+        build_free_from_values(vec![20, 10, 30, 5, 15, 40])
+    }
+
+    fn values<I: Iterator<Item = NodeView<isize, isize>>>(iter: I) -> Vec<isize> {
+        iter.map(|view| view.value).collect()
+    }
+
+    #[test]
+    fn test_pre_order() {
+        assert_eq!(
+            values(setup_tree().pre_order()),
+            vec![20, 10, 5, 15, 30, 40]
+        );
+    }
+
+    #[test]
+    fn test_post_order() {
+        assert_eq!(
+            values(setup_tree().post_order()),
+            vec![5, 15, 10, 40, 30, 20]
+        );
+    }
+
+    #[test]
+    fn test_level_order() {
+        assert_eq!(
+            values(setup_tree().level_order()),
+            vec![20, 10, 30, 5, 15, 40]
+        );
+    }
+
+    #[test]
+    fn test_level_order_matches_convert_node_to_vec() {
+        let tree = build_free_from_values((1..=50).collect());
+        let expected = convert_node_to_vec(tree.root.clone().unwrap());
+        assert_eq!(values(tree.level_order()), Vec::from(expected));
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let tree = IsizeAvlTree::new();
+        assert_eq!(tree.pre_order().next(), None);
+        assert_eq!(tree.post_order().next(), None);
+        assert_eq!(tree.level_order().next(), None);
+    }
+
+    #[test]
+    fn test_node_view_fields() {
+        let mut tree = IsizeAvlTree::new();
+        tree.insert(Node::new(1, 20)).unwrap();
+        tree.insert(Node::new(2, 30)).unwrap();
+        let views: Vec<NodeView<isize, isize>> = tree.pre_order().collect();
+        assert_eq!(
+            views,
+            vec![
+                NodeView {
+                    index: 1,
+                    value: 20,
+                    height: 2,
+                    balance_factor: 1,
+                    depth: 0,
+                },
+                NodeView {
+                    index: 2,
+                    value: 30,
+                    height: 1,
+                    balance_factor: 0,
+                    depth: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_depths() {
+        let depths: Vec<(isize, usize)> = setup_tree()
+            .level_order()
+            .map(|view| (view.value, view.depth))
+            .collect();
+        assert_eq!(
+            depths,
+            vec![(20, 0), (10, 1), (30, 1), (5, 2), (15, 2), (40, 2)]
+        );
+    }
+
+    #[test]
+    fn test_traversals_from_subtree() {
+        let tree = setup_tree();
+        let left_child = tree.root.as_ref().unwrap().borrow().get_left_child();
+        let views: Vec<NodeView<isize, isize>> =
+            PostOrder::from_root(left_child.as_ref()).collect();
+        assert_eq!(values(views.clone().into_iter()), vec![5, 15, 10]);
+        assert_eq!(views[2].depth, 0);
+    }
+}
@@ -1,49 +1,428 @@
-use crate::bst::avl_node::{Node, NodePtr, WeakNodePtr};
-use std::cell::RefCell;
+use crate::bst::avl_entry::{Entry, OccupiedEntry, VacantEntry};
+use crate::bst::avl_node::{Node, NodePtr};
+use crate::bst::comparator::{Comparator, NaturalOrder};
+use crate::bst::duplicate_policy::DuplicatePolicy;
+use crate::bst::node_handle::next_tree_id;
+use crate::bst::summary::Summary;
+use crate::bst::tree_error::TreeError;
+use std::cell::{Ref, RefCell};
+use std::cmp::Ordering;
 use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
 use std::rc::{Rc, Weak};
 
+// Where a key belongs in the tree: the node already holding it, or the leaf it would hang
+// from and whether as a left child (None when the tree is empty)
+pub(crate) enum SearchPosition<K, V, S> {
+    Found(NodePtr<K, V, S>),
+    Vacant(Option<(NodePtr<K, V, S>, bool)>),
+}
+
 #[derive(Debug)]
-pub struct AvlTree {
-    pub root: Option<NodePtr>,
+pub struct AvlTree<K, V, C = NaturalOrder, S = ()> {
+    pub root: Option<NodePtr<K, V, S>>,
+    comparator: C,
+    duplicate_policy: DuplicatePolicy,
+    // Copies the (index, value) of a node, so one entry of a node counted several times can be
+    // handed back while the node keeps the rest; set up along with the duplicate policy
+    copy_entry: Option<CopyEntry<K, V>>,
+    len: usize,
+    // Tells this tree's node handles apart from those of other trees
+    id: usize,
+}
+
+// Read access to one entry of an AvlTree, borrowing the tree so the entry can't be changed or
+// removed meanwhile
+pub struct EntryRef<'a, K, V, S = ()> {
+    node_ptr: NodePtr<K, V, S>,
+    marker: PhantomData<&'a ()>,
+}
+
+// Walks the entries in sorted order through the parent links, from both ends, lending each
+// one through an EntryRef so nothing is copied; a node counted several times is yielded that
+// many times
+pub struct Iter<'a, K, V, S = ()> {
+    front: Option<NodePtr<K, V, S>>,
+    front_taken: usize,
+    back: Option<NodePtr<K, V, S>>,
+    back_taken: usize,
+    remaining: usize,
+    marker: PhantomData<&'a ()>,
+}
+
+// Owns the nodes of a consumed tree, already cut loose from each other and kept in sorted
+// order, and moves their (index, value) payloads out; a node counted several times hands back
+// copies until its last entry, like AvlTree::take_entry does
+pub struct IntoIter<K, V, S = ()> {
+    nodes: VecDeque<NodePtr<K, V, S>>,
+    copy_entry: Option<CopyEntry<K, V>>,
+    remaining: usize,
+}
+
+// Removes matching entries while walking the tree, see AvlTree::extract_if
+pub struct ExtractIf<'a, K, V, C, F, S = ()> {
+    tree: &'a mut AvlTree<K, V, C, S>,
+    next_node: Option<NodePtr<K, V, S>>,
+    // A counted node pred matched, still holding entries to hand out
+    matched_node: Option<NodePtr<K, V, S>>,
+    pred: F,
+}
+
+// Entries whose values fall between two bounds, in sorted order from both ends, lent through
+// EntryRef like Iter does; the walk is over once the front and back positions have met
+pub struct Range<'a, K, V, S = ()> {
+    front: Option<NodePtr<K, V, S>>,
+    front_taken: usize,
+    back: Option<NodePtr<K, V, S>>,
+    back_taken: usize,
+    marker: PhantomData<&'a ()>,
+}
+
+// Every node whose value equals a key, earliest inserted first
+pub struct EqualRange<'a, K, V, C, S = ()> {
+    next_node: Option<NodePtr<K, V, S>>,
+    key: &'a K,
+    comparator: &'a C,
+}
+
+// The tree the crate originally shipped, keyed and indexed by isize
+#[allow(dead_code)]
+pub type IsizeAvlTree = AvlTree<isize, isize>;
+
+// Copies the (index, value) of a node
+type CopyEntry<K, V> = fn(&V, &K) -> (V, K);
+
+// An entry taken from a node, and the node when it still counts more entries
+type SplitEntry<K, V, S> = (Option<(V, K)>, Option<NodePtr<K, V, S>>);
+
+// A node just unlinked from the tree and the node that followed it
+type UnlinkedNode<K, V, S> = (NodePtr<K, V, S>, Option<NodePtr<K, V, S>>);
+
+// The detached subtrees holding the entries before and after a split position
+type SplitSubtrees<K, V, S> = (Option<NodePtr<K, V, S>>, Option<NodePtr<K, V, S>>);
+
+impl<'a, K, V, S> EntryRef<'a, K, V, S> {
+    pub(crate) fn new(node_ptr: NodePtr<K, V, S>) -> EntryRef<'a, K, V, S> {
+        EntryRef {
+            node_ptr,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn value(&self) -> Ref<'_, K> {
+        Ref::map(self.node_ptr.borrow(), |node| &node.value)
+    }
+
+    pub fn index(&self) -> Ref<'_, V> {
+        Ref::map(self.node_ptr.borrow(), |node| &node.index)
+    }
+
+    // The node behind the entry, for the facades that wrap it in their own guard
+    pub(crate) fn into_node_ptr(self) -> NodePtr<K, V, S> {
+        self.node_ptr
+    }
+}
+
+impl<K, V, C: Default, S> Default for AvlTree<K, V, C, S> {
+    fn default() -> Self {
+        AvlTree::summarized(C::default())
+    }
+}
+
+impl<K: Ord, V> AvlTree<K, V> {
+    pub fn new() -> AvlTree<K, V> {
+        AvlTree::with_comparator(NaturalOrder)
+    }
+}
+
+impl<K, V, C> AvlTree<K, V, C> {
+    pub fn with_comparator(comparator: C) -> AvlTree<K, V, C> {
+        AvlTree::summarized(comparator)
+    }
+}
+
+impl<K, V, C, S> AvlTree<K, V, C, S> {
+    // A tree keeping a Summary of every subtree, with S picked by the caller's type
+    pub fn summarized(comparator: C) -> AvlTree<K, V, C, S> {
+        AvlTree {
+            root: None,
+            comparator,
+            duplicate_policy: DuplicatePolicy::default(),
+            copy_entry: None,
+            len: 0,
+            id: next_tree_id(),
+        }
+    }
+
+    // Removing one of the entries a node counts under DuplicatePolicy::Count hands back a copy
+    // of it, so a policy can only be picked when keys and indexes can be cloned
+    pub fn with_duplicate_policy(mut self, duplicate_policy: DuplicatePolicy) -> AvlTree<K, V, C, S>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.duplicate_policy = duplicate_policy;
+        self.copy_entry = Some(clone_entry);
+        self
+    }
+
+    pub fn comparator(&self) -> &C {
+        &self.comparator
+    }
+
+    pub fn duplicate_policy(&self) -> DuplicatePolicy {
+        self.duplicate_policy
+    }
+
+    pub(crate) fn id(&self) -> usize {
+        self.id
+    }
+
+    // Number of entries, counting every duplicate a node stands for under DuplicatePolicy::Count
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn height(&self) -> isize {
+        match &self.root {
+            Some(root_ptr) => root_ptr.borrow().height,
+            None => 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    // Node holding the entry at position k in sorted order, counting from zero; a node counted
+    // several times covers that many positions
+    pub fn select(&self, k: usize) -> Option<NodePtr<K, V, S>> {
+        let mut remaining = k;
+        let mut current_node_ptr = Rc::clone(self.root.as_ref()?);
+        loop {
+            let (left_size, count) = {
+                let node = current_node_ptr.borrow();
+                (node.get_left_child_size(), node.count)
+            };
+            let child_ptr_option = if remaining < left_size {
+                current_node_ptr.borrow().get_left_child()
+            } else if remaining < left_size + count {
+                return Some(current_node_ptr);
+            } else {
+                remaining -= left_size + count;
+                current_node_ptr.borrow().get_right_child()
+            };
+            current_node_ptr = child_ptr_option?;
+        }
+    }
+
+    pub(crate) fn first_node(&self) -> Option<NodePtr<K, V, S>> {
+        let root_ptr = Rc::clone(self.root.as_ref()?);
+        Some(Self::get_minimum_child(root_ptr))
+    }
+
+    pub(crate) fn last_node(&self) -> Option<NodePtr<K, V, S>> {
+        let root_ptr = Rc::clone(self.root.as_ref()?);
+        Some(Self::get_maximum_child(root_ptr))
+    }
+
+    // Smallest entry, the earliest inserted one among equal values
+    pub fn first(&self) -> Option<EntryRef<'_, K, V, S>> {
+        self.first_node().map(EntryRef::new)
+    }
+
+    // Largest entry, the latest inserted one among equal values
+    pub fn last(&self) -> Option<EntryRef<'_, K, V, S>> {
+        self.last_node().map(EntryRef::new)
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V, S> {
+        Iter {
+            front: self.first_node(),
+            front_taken: 0,
+            back: self.last_node(),
+            back_taken: 0,
+            remaining: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn get_minimum_child(node_ptr: NodePtr<K, V, S>) -> NodePtr<K, V, S> {
+        let mut current_node_ptr = node_ptr;
+        loop {
+            let left_child_ptr_option = current_node_ptr.borrow().get_left_child();
+            match left_child_ptr_option {
+                Some(left_child_ptr) => current_node_ptr = left_child_ptr,
+                None => break,
+            }
+        }
+        current_node_ptr
+    }
+
+    pub fn get_maximum_child(node_ptr: NodePtr<K, V, S>) -> NodePtr<K, V, S> {
+        let mut current_node_ptr = node_ptr;
+        loop {
+            let right_child_ptr_option = current_node_ptr.borrow().get_right_child();
+            match right_child_ptr_option {
+                Some(right_child_ptr) => current_node_ptr = right_child_ptr,
+                None => break,
+            }
+        }
+        current_node_ptr
+    }
+
+    // In-order successor of node_ptr, walking up through the parent links when it has no
+    // right subtree
+    pub fn get_next_node(node_ptr: &NodePtr<K, V, S>) -> Option<NodePtr<K, V, S>> {
+        if let Some(right_child_ptr) = node_ptr.borrow().get_right_child() {
+            return Some(Self::get_minimum_child(right_child_ptr));
+        }
+        let mut current_node_ptr = Rc::clone(node_ptr);
+        loop {
+            let parent_ptr = current_node_ptr.borrow().parent.upgrade()?;
+            let is_right_child = match &parent_ptr.borrow().right_child {
+                Some(right_child_ptr) => Rc::ptr_eq(right_child_ptr, &current_node_ptr),
+                None => false,
+            };
+            if !is_right_child {
+                return Some(parent_ptr);
+            }
+            current_node_ptr = parent_ptr;
+        }
+    }
+
+    // In-order predecessor of node_ptr, the mirror image of get_next_node
+    pub fn get_previous_node(node_ptr: &NodePtr<K, V, S>) -> Option<NodePtr<K, V, S>> {
+        if let Some(left_child_ptr) = node_ptr.borrow().get_left_child() {
+            return Some(Self::get_maximum_child(left_child_ptr));
+        }
+        let mut current_node_ptr = Rc::clone(node_ptr);
+        loop {
+            let parent_ptr = current_node_ptr.borrow().parent.upgrade()?;
+            let is_left_child = match &parent_ptr.borrow().left_child {
+                Some(left_child_ptr) => Rc::ptr_eq(left_child_ptr, &current_node_ptr),
+                None => false,
+            };
+            if !is_left_child {
+                return Some(parent_ptr);
+            }
+            current_node_ptr = parent_ptr;
+        }
+    }
 }
 
-impl AvlTree {
-    pub fn new() -> AvlTree {
-        AvlTree { root: None }
+impl<K, V, C: Comparator<K>, S: Summary<K, V>> AvlTree<K, V, C, S> {
+    // Handles a key already in the tree according to the tree's DuplicatePolicy
+    pub fn insert(&mut self, node: Node<K, V, S>) -> Result<(), TreeError> {
+        if self.duplicate_policy == DuplicatePolicy::KeepAll {
+            self.insert_after_equals(node);
+            return Ok(());
+        }
+        let existing_node_ptr = match self.locate(&node.value) {
+            SearchPosition::Found(node_ptr) => node_ptr,
+            SearchPosition::Vacant(position) => {
+                self.attach_node(position, node);
+                return Ok(());
+            }
+        };
+        match self.duplicate_policy {
+            DuplicatePolicy::Reject => return Err(TreeError::DuplicateKey),
+            DuplicatePolicy::Replace => {
+                existing_node_ptr.borrow_mut().index = node.index;
+                Node::refresh_upwards(existing_node_ptr);
+            }
+            _ => {
+                existing_node_ptr.borrow_mut().count += 1;
+                self.len += 1;
+                Node::refresh_upwards(existing_node_ptr);
+            }
+        }
+        Ok(())
+    }
+
+    // Call after changing a node's index or count in place through a NodePtr, so the sizes
+    // and summaries above it catch up
+    pub fn refresh_node(&mut self, node_ptr: &NodePtr<K, V, S>) {
+        Node::refresh_upwards(Rc::clone(node_ptr));
     }
 
-    pub fn insert(&mut self, node: Node) {
+    // Equal keys go to the right, so a duplicate lands after every earlier one in order
+    fn insert_after_equals(&mut self, node: Node<K, V, S>) -> NodePtr<K, V, S> {
         if self.root.is_none() {
-            self.root = Some(Rc::new(RefCell::new(node)));
-            return;
+            return self.attach_node(None, node);
         }
 
         let current_node_ptr = Rc::clone(self.root.as_ref().unwrap());
-        let node_value = node.value;
-        let leaf_node = AvlTree::find_leaf_node_for_insertion(current_node_ptr, node_value);
+        let leaf_node = self.find_leaf_node_for_insertion(current_node_ptr, &node.value);
+
+        let insert_to_left = self
+            .comparator
+            .compare(&node.value, &leaf_node.borrow().value)
+            == Ordering::Less;
+        self.attach_node(Some((leaf_node, insert_to_left)), node)
+    }
+
+    pub fn entry(&mut self, value: K) -> Entry<'_, K, V, C, S> {
+        match self.locate(&value) {
+            SearchPosition::Found(node_ptr) => Entry::Occupied(OccupiedEntry {
+                tree: self,
+                node_ptr,
+            }),
+            SearchPosition::Vacant(position) => Entry::Vacant(VacantEntry {
+                tree: self,
+                value,
+                position,
+            }),
+        }
+    }
 
-        let leaf_node_value: isize = leaf_node.borrow().value;
+    // Hangs node below the given leaf (on its left side when the flag is set), or makes it the
+    // root when no leaf is given, then rebalances upwards
+    pub(crate) fn attach_node(
+        &mut self,
+        position: Option<(NodePtr<K, V, S>, bool)>,
+        node: Node<K, V, S>,
+    ) -> NodePtr<K, V, S> {
+        self.len += node.count;
         let child_ptr = Rc::new(RefCell::new(node));
+        child_ptr.borrow_mut().set_height();
+        let (leaf_node, insert_to_left) = match position {
+            Some(position) => position,
+            None => {
+                self.root = Some(Rc::clone(&child_ptr));
+                return child_ptr;
+            }
+        };
         child_ptr.borrow_mut().parent = Rc::downgrade(&leaf_node);
-        let child_to_add: Option<NodePtr> = Some(Rc::clone(&child_ptr));
-        if node_value < leaf_node_value {
+        let child_to_add: Option<NodePtr<K, V, S>> = Some(Rc::clone(&child_ptr));
+        if insert_to_left {
             leaf_node.borrow_mut().left_child = child_to_add;
         } else {
             leaf_node.borrow_mut().right_child = child_to_add;
         }
         leaf_node.borrow_mut().set_height();
         self.update_balance(leaf_node);
+        child_ptr
     }
 
     fn find_leaf_node_for_insertion(
-        current_node_ptr: NodePtr,
-        insert_node_value: isize,
-    ) -> NodePtr {
+        &self,
+        current_node_ptr: NodePtr<K, V, S>,
+        insert_node_value: &K,
+    ) -> NodePtr<K, V, S> {
         let mut x = current_node_ptr;
         loop {
-            let current_value: isize = x.borrow().value;
-            if insert_node_value < current_value {
+            let go_left = self
+                .comparator
+                .compare(insert_node_value, &x.borrow().value)
+                == Ordering::Less;
+            if go_left {
                 let left_child = x.borrow().get_left_child();
                 match left_child {
                     Some(node_ptr) => x = node_ptr,
@@ -59,7 +438,7 @@ impl AvlTree {
         }
     }
 
-    fn update_balance(&mut self, node_ptr: NodePtr) {
+    fn update_balance(&mut self, node_ptr: NodePtr<K, V, S>) {
         let parent_ptr_option = node_ptr.borrow().parent.upgrade();
 
         if node_ptr.borrow().get_balance_factor() < -1 || node_ptr.borrow().get_balance_factor() > 1
@@ -67,20 +446,15 @@ impl AvlTree {
             self.rebalance(node_ptr);
         }
 
-        if parent_ptr_option.is_some() {
-            let parent_ptr = parent_ptr_option.unwrap();
-
-            let old_height = parent_ptr.borrow().height;
+        // Every ancestor's size changes, so the walk always goes on up to the root, which in a
+        // join is the root of the detached subtree being built rather than of the whole tree
+        if let Some(parent_ptr) = parent_ptr_option {
             parent_ptr.borrow_mut().set_height();
-            let new_height = parent_ptr.borrow().height;
-
-            if old_height != new_height {
-                self.update_balance(parent_ptr);
-            }
+            self.update_balance(parent_ptr);
         }
     }
 
-    fn rebalance(&mut self, node_ptr: NodePtr) {
+    fn rebalance(&mut self, node_ptr: NodePtr<K, V, S>) {
         if node_ptr.borrow().get_balance_factor() > 0 {
             if node_ptr
                 .borrow()
@@ -91,53 +465,68 @@ impl AvlTree {
                 < 0
             {
                 let right_child_ptr = node_ptr.borrow().get_right_child().unwrap();
-                self.right_rotate(right_child_ptr);
-                self.left_rotate(node_ptr);
+                self.rotate_right(right_child_ptr);
+                self.rotate_left(node_ptr);
             } else {
-                self.left_rotate(node_ptr);
+                self.rotate_left(node_ptr);
             }
+        } else if node_ptr
+            .borrow()
+            .get_left_child()
+            .unwrap()
+            .borrow()
+            .get_balance_factor()
+            > 0
+        {
+            let left_child_ptr = node_ptr.borrow().get_left_child().unwrap();
+            self.rotate_left(left_child_ptr);
+            self.rotate_right(node_ptr);
         } else {
-            if node_ptr
-                .borrow()
-                .get_left_child()
-                .unwrap()
-                .borrow()
-                .get_balance_factor()
-                > 0
-            {
-                let left_child_ptr = node_ptr.borrow().get_left_child().unwrap();
-                self.left_rotate(left_child_ptr);
-                self.right_rotate(node_ptr);
-            } else {
-                self.right_rotate(node_ptr);
-            }
+            self.rotate_right(node_ptr);
+        }
+    }
+
+    pub fn left_rotate(&mut self, node_ptr: NodePtr<K, V, S>) -> Result<(), TreeError> {
+        self.check_membership(&node_ptr)?;
+        if node_ptr.borrow().right_child.is_none() {
+            return Err(TreeError::MissingChild);
+        }
+        self.rotate_left(node_ptr);
+        Ok(())
+    }
+
+    pub fn right_rotate(&mut self, node_ptr: NodePtr<K, V, S>) -> Result<(), TreeError> {
+        self.check_membership(&node_ptr)?;
+        if node_ptr.borrow().left_child.is_none() {
+            return Err(TreeError::MissingChild);
         }
+        self.rotate_right(node_ptr);
+        Ok(())
     }
 
-    pub fn left_rotate(&mut self, node_ptr: NodePtr) {
+    // node_ptr must have a right child
+    fn rotate_left(&mut self, node_ptr: NodePtr<K, V, S>) {
         let old_right_child_ptr = node_ptr.borrow().get_right_child().unwrap();
         let new_right_child_ptr_option = old_right_child_ptr.borrow().get_left_child();
-        if new_right_child_ptr_option.is_some() {
-            new_right_child_ptr_option
-                .as_ref()
-                .unwrap()
-                .borrow_mut()
-                .parent = Rc::downgrade(&node_ptr);
+        if let Some(new_right_child_ptr) = &new_right_child_ptr_option {
+            new_right_child_ptr.borrow_mut().parent = Rc::downgrade(&node_ptr);
         }
         node_ptr.borrow_mut().right_child = new_right_child_ptr_option;
 
         let old_parent = node_ptr.borrow().parent.upgrade();
-        if old_parent.is_none() {
-            old_right_child_ptr.borrow_mut().parent = Weak::new();
-            self.root = Some(Rc::clone(&old_right_child_ptr));
-        } else if node_ptr.borrow().is_left_child() {
-            old_parent.as_ref().unwrap().borrow_mut().left_child =
-                Some(Rc::clone(&old_right_child_ptr));
-            old_right_child_ptr.borrow_mut().parent = Rc::downgrade(&old_parent.as_ref().unwrap());
-        } else {
-            old_parent.as_ref().unwrap().borrow_mut().right_child =
-                Some(Rc::clone(&old_right_child_ptr));
-            old_right_child_ptr.borrow_mut().parent = Rc::downgrade(&old_parent.as_ref().unwrap());
+        match old_parent {
+            None => {
+                old_right_child_ptr.borrow_mut().parent = Weak::new();
+                self.root = Some(Rc::clone(&old_right_child_ptr));
+            }
+            Some(old_parent) => {
+                if node_ptr.borrow().is_left_child() {
+                    old_parent.borrow_mut().left_child = Some(Rc::clone(&old_right_child_ptr));
+                } else {
+                    old_parent.borrow_mut().right_child = Some(Rc::clone(&old_right_child_ptr));
+                }
+                old_right_child_ptr.borrow_mut().parent = Rc::downgrade(&old_parent);
+            }
         }
         old_right_child_ptr.borrow_mut().left_child = Some(Rc::clone(&node_ptr));
         node_ptr.borrow_mut().parent = Rc::downgrade(&old_right_child_ptr);
@@ -146,31 +535,29 @@ impl AvlTree {
         old_right_child_ptr.borrow_mut().set_height();
     }
 
-    pub fn right_rotate(&mut self, node_ptr: NodePtr) {
+    // node_ptr must have a left child
+    fn rotate_right(&mut self, node_ptr: NodePtr<K, V, S>) {
         let old_left_child_ptr = node_ptr.borrow().get_left_child().unwrap();
         let new_left_child_ptr_option = old_left_child_ptr.borrow().get_right_child();
-        if new_left_child_ptr_option.is_some() {
-            new_left_child_ptr_option
-                .as_ref()
-                .unwrap()
-                .borrow_mut()
-                .parent = Rc::downgrade(&node_ptr);
+        if let Some(new_left_child_ptr) = &new_left_child_ptr_option {
+            new_left_child_ptr.borrow_mut().parent = Rc::downgrade(&node_ptr);
         }
         node_ptr.borrow_mut().left_child = new_left_child_ptr_option;
 
         let old_parent = node_ptr.borrow().parent.upgrade();
-
-        if old_parent.is_none() {
-            old_left_child_ptr.borrow_mut().parent = Weak::new();
-            self.root = Some(Rc::clone(&old_left_child_ptr));
-        } else if node_ptr.borrow().is_left_child() {
-            old_parent.as_ref().unwrap().borrow_mut().left_child =
-                Some(Rc::clone(&old_left_child_ptr));
-            old_left_child_ptr.borrow_mut().parent = Rc::downgrade(&old_parent.as_ref().unwrap());
-        } else {
-            old_parent.as_ref().unwrap().borrow_mut().right_child =
-                Some(Rc::clone(&old_left_child_ptr));
-            old_left_child_ptr.borrow_mut().parent = Rc::downgrade(&old_parent.as_ref().unwrap());
+        match old_parent {
+            None => {
+                old_left_child_ptr.borrow_mut().parent = Weak::new();
+                self.root = Some(Rc::clone(&old_left_child_ptr));
+            }
+            Some(old_parent) => {
+                if node_ptr.borrow().is_left_child() {
+                    old_parent.borrow_mut().left_child = Some(Rc::clone(&old_left_child_ptr));
+                } else {
+                    old_parent.borrow_mut().right_child = Some(Rc::clone(&old_left_child_ptr));
+                }
+                old_left_child_ptr.borrow_mut().parent = Rc::downgrade(&old_parent);
+            }
         }
         old_left_child_ptr.borrow_mut().right_child = Some(Rc::clone(&node_ptr));
         node_ptr.borrow_mut().parent = Rc::downgrade(&old_left_child_ptr);
@@ -179,57 +566,263 @@ impl AvlTree {
         old_left_child_ptr.borrow_mut().set_height();
     }
 
-    pub fn search_tree_by_value(&self, value: isize) -> Option<NodePtr> {
-        if self.root.is_none() {
-            return None;
+    pub fn search_tree_by_value(&self, value: &K) -> Option<NodePtr<K, V, S>> {
+        match self.locate(value) {
+            SearchPosition::Found(node_ptr) => Some(node_ptr),
+            SearchPosition::Vacant(_) => None,
         }
+    }
 
-        let mut current_node_ptr = Rc::clone(self.root.as_ref().unwrap());
-        let result = loop {
-            if value < current_node_ptr.borrow().value {
-                let left_child_ptr_option = current_node_ptr.borrow().get_left_child();
-                match left_child_ptr_option {
-                    Some(left_child_ptr) => current_node_ptr = left_child_ptr,
-                    None => break None,
+    pub fn equal_range<'a>(&'a self, key: &'a K) -> EqualRange<'a, K, V, C, S> {
+        let first_node = match self.locate(key) {
+            SearchPosition::Found(node_ptr) => Some(node_ptr),
+            SearchPosition::Vacant(_) => None,
+        };
+        EqualRange {
+            next_node: first_node,
+            key,
+            comparator: &self.comparator,
+        }
+    }
+
+    // Panics like BTreeMap::range when the start is after the end, or when both are the same
+    // excluded value
+    pub fn range<R: RangeBounds<K>>(&self, bounds: R) -> Range<'_, K, V, S> {
+        let start = bounds.start_bound();
+        let end = bounds.end_bound();
+        self.check_range_bounds(start, end);
+
+        // Bounds that fall between two neighbouring entries leave the first node after the last
+        let (front, back) = match (self.first_node_after(start), self.last_node_before(end)) {
+            (Some(first_node_ptr), Some(last_node_ptr))
+                if self.comparator.compare(
+                    &first_node_ptr.borrow().value,
+                    &last_node_ptr.borrow().value,
+                ) != Ordering::Greater =>
+            {
+                (Some(first_node_ptr), Some(last_node_ptr))
+            }
+            _ => (None, None),
+        };
+        Range {
+            front,
+            front_taken: 0,
+            back,
+            back_taken: 0,
+            marker: PhantomData,
+        }
+    }
+
+    // Combined summary of every entry inside bounds, in sorted order, built from O(log n)
+    // stored subtree summaries. Panics on the same bounds range does
+    pub fn aggregate<R: RangeBounds<K>>(&self, bounds: R) -> S {
+        let start = bounds.start_bound();
+        let end = bounds.end_bound();
+        self.check_range_bounds(start, end);
+
+        // Above the highest node inside the range both bounds follow the same path; from
+        // there the left subtree only needs the start checked and the right one only the end
+        let mut current_node_ptr_option = self.root.as_ref().map(Rc::clone);
+        while let Some(current_node_ptr) = current_node_ptr_option {
+            let node = current_node_ptr.borrow();
+            current_node_ptr_option = if !self.is_after_start(&node.value, start) {
+                node.get_right_child()
+            } else if !self.is_before_end(&node.value, end) {
+                node.get_left_child()
+            } else {
+                let left_summary = self.aggregate_after_start(node.get_left_child(), start);
+                let right_summary = self.aggregate_before_end(node.get_right_child(), end);
+                return S::combine(
+                    &S::combine(&left_summary, node.get_own_summary()),
+                    &right_summary,
+                );
+            };
+        }
+        S::identity()
+    }
+
+    // Summary of the entries of a subtree not below start; every piece found lies before the
+    // pieces found higher up
+    fn aggregate_after_start(&self, subtree: Option<NodePtr<K, V, S>>, start: Bound<&K>) -> S {
+        let mut summary = S::identity();
+        let mut current_node_ptr_option = subtree;
+        while let Some(current_node_ptr) = current_node_ptr_option {
+            let node = current_node_ptr.borrow();
+            current_node_ptr_option = if self.is_after_start(&node.value, start) {
+                let own_and_right_summary =
+                    S::combine(node.get_own_summary(), &node.get_right_child_summary());
+                summary = S::combine(&own_and_right_summary, &summary);
+                node.get_left_child()
+            } else {
+                node.get_right_child()
+            };
+        }
+        summary
+    }
+
+    // Summary of the entries of a subtree not above end; every piece found lies after the
+    // pieces found higher up
+    fn aggregate_before_end(&self, subtree: Option<NodePtr<K, V, S>>, end: Bound<&K>) -> S {
+        let mut summary = S::identity();
+        let mut current_node_ptr_option = subtree;
+        while let Some(current_node_ptr) = current_node_ptr_option {
+            let node = current_node_ptr.borrow();
+            current_node_ptr_option = if self.is_before_end(&node.value, end) {
+                let left_and_own_summary =
+                    S::combine(&node.get_left_child_summary(), node.get_own_summary());
+                summary = S::combine(&summary, &left_and_own_summary);
+                node.get_right_child()
+            } else {
+                node.get_left_child()
+            };
+        }
+        summary
+    }
+
+    fn check_range_bounds(&self, start: Bound<&K>, end: Bound<&K>) {
+        if let (
+            Bound::Included(start_value) | Bound::Excluded(start_value),
+            Bound::Included(end_value) | Bound::Excluded(end_value),
+        ) = (start, end)
+        {
+            match self.comparator.compare(start_value, end_value) {
+                Ordering::Greater => panic!("range start is greater than range end"),
+                Ordering::Equal
+                    if matches!(start, Bound::Excluded(_)) && matches!(end, Bound::Excluded(_)) =>
+                {
+                    panic!("range start and end are equal and excluded")
                 }
-            } else if value > current_node_ptr.borrow().value {
-                let right_child_ptr_option = current_node_ptr.borrow().get_right_child();
-                match right_child_ptr_option {
-                    Some(right_child_ptr) => current_node_ptr = right_child_ptr,
-                    None => break None,
+                _ => {}
+            }
+        }
+    }
+
+    fn is_after_start(&self, value: &K, start: Bound<&K>) -> bool {
+        match start {
+            Bound::Included(start_value) => {
+                self.comparator.compare(value, start_value) != Ordering::Less
+            }
+            Bound::Excluded(start_value) => {
+                self.comparator.compare(value, start_value) == Ordering::Greater
+            }
+            Bound::Unbounded => true,
+        }
+    }
+
+    fn is_before_end(&self, value: &K, end: Bound<&K>) -> bool {
+        match end {
+            Bound::Included(end_value) => {
+                self.comparator.compare(value, end_value) != Ordering::Greater
+            }
+            Bound::Excluded(end_value) => {
+                self.comparator.compare(value, end_value) == Ordering::Less
+            }
+            Bound::Unbounded => true,
+        }
+    }
+
+    // How many entries have a value below key
+    pub fn rank(&self, key: &K) -> usize {
+        let mut rank = 0;
+        let mut current_node_ptr_option = self.root.as_ref().map(Rc::clone);
+        while let Some(current_node_ptr) = current_node_ptr_option {
+            let node = current_node_ptr.borrow();
+            current_node_ptr_option = match self.comparator.compare(key, &node.value) {
+                Ordering::Greater => {
+                    rank += node.get_left_child_size() + node.count;
+                    node.get_right_child()
                 }
+                Ordering::Less | Ordering::Equal => node.get_left_child(),
+            };
+        }
+        rank
+    }
+
+    // Latest node whose value is below key
+    pub fn predecessor(&self, key: &K) -> Option<NodePtr<K, V, S>> {
+        self.last_node_before(Bound::Excluded(key))
+    }
+
+    // Earliest node whose value is above key
+    pub fn successor(&self, key: &K) -> Option<NodePtr<K, V, S>> {
+        self.first_node_after(Bound::Excluded(key))
+    }
+
+    // Latest node whose value is not above key
+    pub fn floor(&self, key: &K) -> Option<NodePtr<K, V, S>> {
+        self.last_node_before(Bound::Included(key))
+    }
+
+    // Earliest node whose value is not below key
+    pub fn ceiling(&self, key: &K) -> Option<NodePtr<K, V, S>> {
+        self.first_node_after(Bound::Included(key))
+    }
+
+    // Earliest node not below a lower bound
+    pub(crate) fn first_node_after(&self, bound: Bound<&K>) -> Option<NodePtr<K, V, S>> {
+        let mut found_node_ptr = None;
+        let mut current_node_ptr_option = self.root.as_ref().map(Rc::clone);
+        while let Some(current_node_ptr) = current_node_ptr_option {
+            if self.is_after_start(&current_node_ptr.borrow().value, bound) {
+                current_node_ptr_option = current_node_ptr.borrow().get_left_child();
+                found_node_ptr = Some(current_node_ptr);
             } else {
-                break Some(current_node_ptr);
+                current_node_ptr_option = current_node_ptr.borrow().get_right_child();
             }
-        };
-        result
+        }
+        found_node_ptr
     }
 
-    pub fn get_minimum_child(node_ptr: NodePtr) -> NodePtr {
-        let mut current_node_ptr = node_ptr;
-        loop {
-            let left_child_ptr_option = current_node_ptr.borrow().get_left_child();
-            match left_child_ptr_option {
-                Some(left_child_ptr) => current_node_ptr = left_child_ptr,
-                None => break,
+    // Latest node not above an upper bound
+    pub(crate) fn last_node_before(&self, bound: Bound<&K>) -> Option<NodePtr<K, V, S>> {
+        let mut found_node_ptr = None;
+        let mut current_node_ptr_option = self.root.as_ref().map(Rc::clone);
+        while let Some(current_node_ptr) = current_node_ptr_option {
+            if self.is_before_end(&current_node_ptr.borrow().value, bound) {
+                current_node_ptr_option = current_node_ptr.borrow().get_right_child();
+                found_node_ptr = Some(current_node_ptr);
+            } else {
+                current_node_ptr_option = current_node_ptr.borrow().get_left_child();
             }
         }
-        current_node_ptr
+        found_node_ptr
     }
 
-    pub fn get_maximum_child(node_ptr: NodePtr) -> NodePtr {
-        let mut current_node_ptr = node_ptr;
+    // Single descent shared by searching and the entry API; among duplicates it finds the
+    // earliest inserted one by carrying on to the left after a match
+    pub(crate) fn locate(&self, value: &K) -> SearchPosition<K, V, S> {
+        let mut current_node_ptr = match &self.root {
+            Some(root_ptr) => Rc::clone(root_ptr),
+            None => return SearchPosition::Vacant(None),
+        };
+        let mut found_node_ptr = None;
         loop {
-            let right_child_ptr_option = current_node_ptr.borrow().get_right_child();
-            match right_child_ptr_option {
-                Some(right_child_ptr) => current_node_ptr = right_child_ptr,
-                None => break,
+            let ordering = self
+                .comparator
+                .compare(value, &current_node_ptr.borrow().value);
+            if ordering == Ordering::Equal {
+                found_node_ptr = Some(Rc::clone(&current_node_ptr));
+            }
+            let child_ptr_option = match ordering {
+                Ordering::Less | Ordering::Equal => current_node_ptr.borrow().get_left_child(),
+                Ordering::Greater => current_node_ptr.borrow().get_right_child(),
+            };
+            match child_ptr_option {
+                Some(child_ptr) => current_node_ptr = child_ptr,
+                None => {
+                    break match found_node_ptr {
+                        Some(found_node_ptr) => SearchPosition::Found(found_node_ptr),
+                        None => SearchPosition::Vacant(Some((
+                            current_node_ptr,
+                            ordering == Ordering::Less,
+                        ))),
+                    }
+                }
             }
         }
-        current_node_ptr
     }
 
-    fn delete_root(&mut self) {
+    fn delete_root(&mut self) -> NodePtr<K, V, S> {
         let root_ptr = Rc::clone(self.root.as_ref().unwrap());
         if root_ptr.borrow().has_no_child() {
             self.root = None;
@@ -242,54 +835,386 @@ impl AvlTree {
             right_child.borrow_mut().parent = Weak::new();
             self.root = Some(right_child);
         } else {
-            self.delete_node_with_children(root_ptr);
+            return self.delete_node_with_children(root_ptr);
         }
+        root_ptr
     }
 
-    fn delete_node_with_children(&mut self, node_ptr: NodePtr) {
-        let min_node = AvlTree::get_minimum_child(node_ptr.borrow().get_right_child().unwrap());
-        node_ptr.borrow_mut().value = min_node.borrow().value;
-        node_ptr.borrow_mut().index = min_node.borrow().index;
-        self.delete_node(min_node);
+    fn delete_node_with_children(&mut self, node_ptr: NodePtr<K, V, S>) -> NodePtr<K, V, S> {
+        // The in-order successor node itself moves into node_ptr's place, so every node keeps
+        // its payload and handles to other nodes stay valid
+        let right_child_ptr = node_ptr.borrow().get_right_child().unwrap();
+        let successor_ptr = Self::get_minimum_child(Rc::clone(&right_child_ptr));
+        let rebalance_from = if Rc::ptr_eq(&successor_ptr, &right_child_ptr) {
+            Rc::clone(&successor_ptr)
+        } else {
+            let successor_parent_ptr = successor_ptr.borrow().parent.upgrade().unwrap();
+            let successor_right_child = successor_ptr.borrow().get_right_child();
+            Self::set_new_child(&successor_parent_ptr, &successor_ptr, successor_right_child);
+            right_child_ptr.borrow_mut().parent = Rc::downgrade(&successor_ptr);
+            successor_ptr.borrow_mut().right_child = Some(right_child_ptr);
+            successor_parent_ptr
+        };
+
+        let left_child_ptr = node_ptr.borrow().get_left_child().unwrap();
+        left_child_ptr.borrow_mut().parent = Rc::downgrade(&successor_ptr);
+        successor_ptr.borrow_mut().left_child = Some(left_child_ptr);
+        // Stands in for the height of the slot until the walk below recomputes it
+        successor_ptr.borrow_mut().height = node_ptr.borrow().height;
+        let parent_ptr_option = node_ptr.borrow().parent.upgrade();
+        match parent_ptr_option {
+            Some(parent_ptr) => Self::set_new_child(&parent_ptr, &node_ptr, Some(successor_ptr)),
+            None => {
+                successor_ptr.borrow_mut().parent = Weak::new();
+                self.root = Some(successor_ptr);
+            }
+        }
+
+        rebalance_from.borrow_mut().set_height();
+        self.update_balance(rebalance_from);
+        node_ptr
     }
 
-    // node_ptr must refers to a valid node in the tree
-    pub fn delete_node(&mut self, node_ptr: NodePtr) {
-        if self
-            .root
-            .as_ref()
-            .unwrap()
-            .borrow()
-            .same_structure_to(&node_ptr)
+    // Removes one entry whose value equals key and hands back its (index, value); under
+    // DuplicatePolicy::Count that lowers the count of the key's node by one. Returns None when
+    // no entry matches, or when the node is still held through a NodePtr outside the tree, in
+    // which case the tree is left as it was
+    pub fn remove(&mut self, key: &K) -> Option<(V, K)> {
+        let node_ptr = self.search_tree_by_value(key)?;
+        self.take_entry(node_ptr).ok()
+    }
+
+    pub fn remove_min(&mut self) -> Option<(V, K)> {
+        self.pop_first()
+    }
+
+    pub fn remove_max(&mut self) -> Option<(V, K)> {
+        self.pop_last()
+    }
+
+    // Takes the entry first returns out of the tree, like remove
+    pub fn pop_first(&mut self) -> Option<(V, K)> {
+        let node_ptr = self.first_node()?;
+        self.take_entry(node_ptr).ok()
+    }
+
+    // Takes the entry last returns out of the tree, like remove
+    pub fn pop_last(&mut self) -> Option<(V, K)> {
+        let node_ptr = self.last_node()?;
+        self.take_entry(node_ptr).ok()
+    }
+
+    // Takes one entry of node_ptr out of the tree, the way every removal that hands entries
+    // back does. A node counted several times keeps the rest of its count and gives a copy of
+    // its entry; otherwise the node is unlinked and its payload moved out, which can't be done
+    // while a NodePtr outside the tree still holds it, so then the tree is left as it was
+    pub(crate) fn take_entry(&mut self, node_ptr: NodePtr<K, V, S>) -> Result<(V, K), TreeError> {
+        // Counts only go above one under DuplicatePolicy::Count, which sets up copy_entry; a
+        // count raised by hand in a tree without it goes out along with the node
+        let is_counted = node_ptr.borrow().count > 1;
+        if let (true, Some(copy_entry)) = (is_counted, self.copy_entry) {
+            let entry = {
+                let node = node_ptr.borrow();
+                copy_entry(&node.index, &node.value)
+            };
+            node_ptr.borrow_mut().count -= 1;
+            self.len -= 1;
+            Node::refresh_upwards(node_ptr);
+            return Ok(entry);
+        }
+        // One reference is the link the tree holds the node by, the other one is node_ptr
+        if Rc::strong_count(&node_ptr) > 2 {
+            return Err(TreeError::NodeInUse);
+        }
+        let removed_node_ptr = self.unlink_node(node_ptr);
+        Node::try_into_payload(removed_node_ptr).map_err(|_| TreeError::NodeInUse)
+    }
+
+    pub fn delete_node(&mut self, node_ptr: NodePtr<K, V, S>) -> Result<(), TreeError> {
+        self.check_membership(&node_ptr)?;
+        self.unlink_node(node_ptr);
+        Ok(())
+    }
+
+    // Walks from node_ptr up to the root, checking every parent still links back to the child
+    pub(crate) fn check_membership(&self, node_ptr: &NodePtr<K, V, S>) -> Result<(), TreeError> {
+        if node_ptr.borrow().detached {
+            return Err(TreeError::StaleHandle);
+        }
+        let root_ptr = self.root.as_ref().ok_or(TreeError::EmptyTree)?;
+        let mut current_node_ptr = Rc::clone(node_ptr);
+        loop {
+            let parent_ptr_option = current_node_ptr.borrow().parent.upgrade();
+            let parent_ptr = match parent_ptr_option {
+                Some(parent_ptr) => parent_ptr,
+                None if Rc::ptr_eq(&current_node_ptr, root_ptr) => return Ok(()),
+                None => return Err(TreeError::ForeignNode),
+            };
+            let is_linked = [
+                &parent_ptr.borrow().left_child,
+                &parent_ptr.borrow().right_child,
+            ]
+            .into_iter()
+            .flatten()
+            .any(|child_ptr| Rc::ptr_eq(child_ptr, &current_node_ptr));
+            if !is_linked {
+                return Err(TreeError::StaleHandle);
+            }
+            current_node_ptr = parent_ptr;
+        }
+    }
+
+    // Removes node_ptr from the tree and hands it back without any links, marked as detached
+    // so membership checks report it as stale
+    pub(crate) fn unlink_node(&mut self, node_ptr: NodePtr<K, V, S>) -> NodePtr<K, V, S> {
+        let removed_node_ptr = self.detach_node(node_ptr);
+        self.len -= removed_node_ptr.borrow().count;
         {
-            self.delete_root();
-            return;
+            let mut removed_node = removed_node_ptr.borrow_mut();
+            removed_node.parent = Weak::new();
+            removed_node.left_child = None;
+            removed_node.right_child = None;
+            removed_node.detached = true;
+        }
+        removed_node_ptr
+    }
+
+    // Unlinks node_ptr like unlink_node and also returns the node that followed it, so a walk
+    // over the tree can carry on from there
+    pub(crate) fn unlink_and_advance(
+        &mut self,
+        node_ptr: NodePtr<K, V, S>,
+    ) -> UnlinkedNode<K, V, S> {
+        let next_node = Self::get_next_node(&node_ptr);
+        (self.unlink_node(node_ptr), next_node)
+    }
+
+    // Keeps only the entries keep returns true for, visiting them in sorted order; a node
+    // counted several times is kept or removed as a whole
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut keep: F) {
+        let mut current_node_ptr_option = self.first_node();
+        while let Some(current_node_ptr) = current_node_ptr_option {
+            let is_kept = {
+                let mut node = current_node_ptr.borrow_mut();
+                let node = &mut *node;
+                let is_kept = keep(&node.value, &mut node.index);
+                // keep may have changed the index the summaries depend on
+                node.refresh_own_summary();
+                is_kept
+            };
+            current_node_ptr_option = if is_kept {
+                Self::get_next_node(&current_node_ptr)
+            } else {
+                self.unlink_and_advance(current_node_ptr).1
+            };
+        }
+        // The subtree summaries are folded again once at the end, in O(n), rather than from
+        // every kept node up to the root
+        if has_summary::<K, V, S>() {
+            self.refresh_summaries();
+        }
+    }
+
+    // Runs set_height on every node, children before their parents
+    fn refresh_summaries(&self) {
+        let mut stack: Vec<(NodePtr<K, V, S>, bool)> = self
+            .root
+            .iter()
+            .map(|root_ptr| (Rc::clone(root_ptr), false))
+            .collect();
+        while let Some((node_ptr, children_pushed)) = stack.pop() {
+            if children_pushed {
+                node_ptr.borrow_mut().set_height();
+                continue;
+            }
+            let left_child = node_ptr.borrow().get_left_child();
+            let right_child = node_ptr.borrow().get_right_child();
+            stack.push((node_ptr, true));
+            stack.extend(right_child.map(|child_ptr| (child_ptr, false)));
+            stack.extend(left_child.map(|child_ptr| (child_ptr, false)));
+        }
+    }
+
+    // Lazily removes and yields the entries pred returns true for, in sorted order. pred is
+    // asked once per node, and every entry of a matched counted node is handed out. Entries not
+    // reached before the iterator is dropped stay in the tree, as do those of a node still held
+    // through a NodePtr outside the tree. A node pred keeps has its summaries refreshed up to
+    // the root, unless the tree keeps none
+    pub fn extract_if<F: FnMut(&K, &mut V) -> bool>(
+        &mut self,
+        pred: F,
+    ) -> ExtractIf<'_, K, V, C, F, S> {
+        let next_node = self.first_node();
+        ExtractIf {
+            tree: self,
+            next_node,
+            matched_node: None,
+            pred,
+        }
+    }
+
+    // Moves every entry from position k on into a new tree sharing this tree's comparator and
+    // policy. The joins split_subtree makes cost O(log n) together, see join_subtrees. A node
+    // counted several times goes along with its first position
+    pub(crate) fn split_off_at(&mut self, k: usize) -> AvlTree<K, V, C, S>
+    where
+        C: Clone,
+    {
+        let root = self.root.take();
+        let (left_root, right_root) = self.split_subtree(root, k);
+        let mut right_tree = AvlTree::summarized(self.comparator.clone());
+        right_tree.duplicate_policy = self.duplicate_policy;
+        right_tree.copy_entry = self.copy_entry;
+        self.len = subtree_size(&left_root);
+        self.root = left_root;
+        right_tree.len = subtree_size(&right_root);
+        right_tree.root = right_root;
+        right_tree
+    }
+
+    // Moves every entry of other after the entries of this tree, in O(log n): one removal from
+    // other and one join. The caller makes sure this keeps the order, as nothing is compared
+    pub(crate) fn append_tree(&mut self, other: &mut AvlTree<K, V, C, S>) {
+        let middle_ptr = match other.first_node() {
+            Some(first_node_ptr) => other.unlink_node(first_node_ptr),
+            None => return,
+        };
+        let len = self.len + middle_ptr.borrow().count + other.len;
+        let (left_root, right_root) = (self.root.take(), other.root.take());
+        let joined_root = self.join_subtrees(left_root, middle_ptr, right_root);
+        self.root = Some(joined_root);
+        self.len = len;
+        other.clear();
+    }
+
+    // Cuts a detached subtree into the entries before position k and the rest, by splitting
+    // the side holding k and joining the other side back on through the root. Each side only
+    // ever gets joined with subtrees at least as tall as it is, so the height differences the
+    // joins pay for add up to the height of the whole subtree
+    fn split_subtree(
+        &mut self,
+        root: Option<NodePtr<K, V, S>>,
+        k: usize,
+    ) -> SplitSubtrees<K, V, S> {
+        let root_ptr = match root {
+            Some(root_ptr) => root_ptr,
+            None => return (None, None),
+        };
+        let left_child = root_ptr.borrow_mut().left_child.take();
+        let right_child = root_ptr.borrow_mut().right_child.take();
+        for child_ptr in left_child.iter().chain(right_child.iter()) {
+            child_ptr.borrow_mut().parent = Weak::new();
+        }
+        let left_size = subtree_size(&left_child);
+        if k <= left_size {
+            let (before, after) = self.split_subtree(left_child, k);
+            (
+                before,
+                Some(self.join_subtrees(after, root_ptr, right_child)),
+            )
+        } else {
+            let right_k = k.saturating_sub(left_size + root_ptr.borrow().count);
+            let (before, after) = self.split_subtree(right_child, right_k);
+            (
+                Some(self.join_subtrees(left_child, root_ptr, before)),
+                after,
+            )
+        }
+    }
+
+    // Joins two detached subtrees through middle_ptr, which must have no children, and returns
+    // the new root. When one side is more than one level taller, middle_ptr replaces the first
+    // node on its inner spine short enough to sit next to the other side, and the usual
+    // rebalancing walk fixes the heights above. That walk ends at the taller side's root, which
+    // has no parent, so the descent and the walk both take O(|left height - right height| + 1)
+    // steps. The tree's root is only borrowed meanwhile, as the rotations hang their result
+    // there
+    fn join_subtrees(
+        &mut self,
+        left: Option<NodePtr<K, V, S>>,
+        middle_ptr: NodePtr<K, V, S>,
+        right: Option<NodePtr<K, V, S>>,
+    ) -> NodePtr<K, V, S> {
+        let left_height = subtree_height(&left);
+        let right_height = subtree_height(&right);
+        if left_height > right_height + 1 {
+            let mut parent_ptr = Rc::clone(left.as_ref().unwrap());
+            let mut inner_option = parent_ptr.borrow().get_right_child();
+            while subtree_height(&inner_option) > right_height + 1 {
+                parent_ptr = inner_option.unwrap();
+                inner_option = parent_ptr.borrow().get_right_child();
+            }
+            Self::hang_children(&middle_ptr, inner_option, right);
+            middle_ptr.borrow_mut().parent = Rc::downgrade(&parent_ptr);
+            parent_ptr.borrow_mut().right_child = Some(Rc::clone(&middle_ptr));
+            self.root = left;
+        } else if right_height > left_height + 1 {
+            let mut parent_ptr = Rc::clone(right.as_ref().unwrap());
+            let mut inner_option = parent_ptr.borrow().get_left_child();
+            while subtree_height(&inner_option) > left_height + 1 {
+                parent_ptr = inner_option.unwrap();
+                inner_option = parent_ptr.borrow().get_left_child();
+            }
+            Self::hang_children(&middle_ptr, left, inner_option);
+            middle_ptr.borrow_mut().parent = Rc::downgrade(&parent_ptr);
+            parent_ptr.borrow_mut().left_child = Some(Rc::clone(&middle_ptr));
+            self.root = right;
+        } else {
+            Self::hang_children(&middle_ptr, left, right);
+            middle_ptr.borrow_mut().parent = Weak::new();
+            return middle_ptr;
+        }
+        self.update_balance(middle_ptr);
+        self.root.take().unwrap()
+    }
+
+    fn hang_children(
+        node_ptr: &NodePtr<K, V, S>,
+        left: Option<NodePtr<K, V, S>>,
+        right: Option<NodePtr<K, V, S>>,
+    ) {
+        for child_ptr in left.iter().chain(right.iter()) {
+            child_ptr.borrow_mut().parent = Rc::downgrade(node_ptr);
+        }
+        let mut node = node_ptr.borrow_mut();
+        node.left_child = left;
+        node.right_child = right;
+        node.set_height();
+    }
+
+    fn detach_node(&mut self, node_ptr: NodePtr<K, V, S>) -> NodePtr<K, V, S> {
+        if Rc::ptr_eq(self.root.as_ref().unwrap(), &node_ptr) {
+            return self.delete_root();
         }
 
         if node_ptr.borrow().has_both_children() {
-            self.delete_node_with_children(node_ptr);
-            return;
+            return self.delete_node_with_children(node_ptr);
         }
 
         // Here we know the node_ptr is not the root, so the parent can not be None
         let parent = node_ptr.borrow().parent.upgrade().unwrap();
         // Here we know the node_ptr can have at most one child
         if node_ptr.borrow().has_no_child() {
-            AvlTree::set_new_child(&parent, node_ptr, None);
+            Self::set_new_child(&parent, &node_ptr, None);
         } else if node_ptr.borrow().has_only_left_child() {
             let node_ptr_left_child = node_ptr.borrow().get_left_child();
-            AvlTree::set_new_child(&parent, node_ptr, node_ptr_left_child);
+            Self::set_new_child(&parent, &node_ptr, node_ptr_left_child);
         } else {
             let node_ptr_right_child = node_ptr.borrow().get_right_child();
-            AvlTree::set_new_child(&parent, node_ptr, node_ptr_right_child);
+            Self::set_new_child(&parent, &node_ptr, node_ptr_right_child);
         }
         parent.borrow_mut().set_height();
         self.update_balance(parent);
+        node_ptr
     }
 
-    fn set_new_child(parent: &NodePtr, old_child: NodePtr, new_child: Option<NodePtr>) {
-        if new_child.is_some() {
-            new_child.as_ref().unwrap().borrow_mut().parent = Rc::downgrade(parent);
+    fn set_new_child(
+        parent: &NodePtr<K, V, S>,
+        old_child: &NodePtr<K, V, S>,
+        new_child: Option<NodePtr<K, V, S>>,
+    ) {
+        if let Some(new_child_ptr) = &new_child {
+            new_child_ptr.borrow_mut().parent = Rc::downgrade(parent);
         }
         if old_child.borrow().is_left_child() {
             parent.borrow_mut().left_child = new_child;
@@ -299,13 +1224,309 @@ impl AvlTree {
     }
 }
 
-pub fn convert_node_to_vec(node_ptr: NodePtr) -> VecDeque<isize> {
-    let mut value_vec: VecDeque<isize> = VecDeque::new();
-    let mut node_vec: VecDeque<NodePtr> = VecDeque::from(vec![node_ptr]);
+impl<'a, K, V, C, S> IntoIterator for &'a AvlTree<K, V, C, S> {
+    type Item = EntryRef<'a, K, V, S>;
+    type IntoIter = Iter<'a, K, V, S>;
+
+    fn into_iter(self) -> Iter<'a, K, V, S> {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> Iterator for Iter<'a, K, V, S> {
+    type Item = EntryRef<'a, K, V, S>;
+
+    fn next(&mut self) -> Option<EntryRef<'a, K, V, S>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node_ptr = Rc::clone(self.front.as_ref()?);
+        self.front_taken += 1;
+        if self.front_taken == node_ptr.borrow().count {
+            self.front = AvlTree::<K, V, NaturalOrder, S>::get_next_node(&node_ptr);
+            self.front_taken = 0;
+        }
+        Some(EntryRef::new(node_ptr))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V, S> DoubleEndedIterator for Iter<'a, K, V, S> {
+    fn next_back(&mut self) -> Option<EntryRef<'a, K, V, S>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node_ptr = Rc::clone(self.back.as_ref()?);
+        self.back_taken += 1;
+        if self.back_taken == node_ptr.borrow().count {
+            self.back = AvlTree::<K, V, NaturalOrder, S>::get_previous_node(&node_ptr);
+            self.back_taken = 0;
+        }
+        Some(EntryRef::new(node_ptr))
+    }
+}
+
+impl<'a, K, V, S> ExactSizeIterator for Iter<'a, K, V, S> {}
+
+// A node still held through a NodePtr outside the tree keeps its entries, so they are left out
+impl<K, V, C, S> IntoIterator for AvlTree<K, V, C, S> {
+    type Item = (V, K);
+    type IntoIter = IntoIter<K, V, S>;
+
+    fn into_iter(self) -> IntoIter<K, V, S> {
+        let copy_entry = self.copy_entry;
+        let nodes = self.into_sorted_nodes();
+        let remaining = nodes
+            .iter()
+            .map(|node_ptr| match copy_entry {
+                Some(_) => node_ptr.borrow().count,
+                None => 1,
+            })
+            .sum();
+        IntoIter {
+            nodes,
+            copy_entry,
+            remaining,
+        }
+    }
+}
+
+impl<K, V, C, S> AvlTree<K, V, C, S> {
+    // Cuts every node loose in sorted order, marking each one detached, and keeps those nothing
+    // outside the tree holds
+    fn into_sorted_nodes(mut self) -> VecDeque<NodePtr<K, V, S>> {
+        let mut nodes = VecDeque::with_capacity(self.len);
+        let mut stack = Vec::new();
+        let mut current_node_ptr_option = self.root.take();
+        // Taking every child link on the way leaves each node owned by the queue alone
+        loop {
+            while let Some(current_node_ptr) = current_node_ptr_option {
+                current_node_ptr_option = current_node_ptr.borrow_mut().left_child.take();
+                stack.push(current_node_ptr);
+            }
+            let Some(node_ptr) = stack.pop() else {
+                break;
+            };
+            current_node_ptr_option = node_ptr.borrow_mut().right_child.take();
+            {
+                let mut node = node_ptr.borrow_mut();
+                node.parent = Weak::new();
+                node.detached = true;
+            }
+            if Rc::strong_count(&node_ptr) == 1 {
+                nodes.push_back(node_ptr);
+            }
+        }
+        nodes
+    }
+}
 
-    while node_vec.len() > 0 {
-        let node_ptr = node_vec.pop_front().unwrap();
-        value_vec.push_back(node_ptr.borrow().value);
+impl<K, V, S> IntoIter<K, V, S> {
+    // One entry of node_ptr, along with the node itself when it still counts more entries
+    fn split_entry(&mut self, node_ptr: NodePtr<K, V, S>) -> SplitEntry<K, V, S> {
+        self.remaining -= 1;
+        let is_counted = node_ptr.borrow().count > 1;
+        if let (true, Some(copy_entry)) = (is_counted, self.copy_entry) {
+            let entry = {
+                let node = node_ptr.borrow();
+                copy_entry(&node.index, &node.value)
+            };
+            node_ptr.borrow_mut().count -= 1;
+            return (Some(entry), Some(node_ptr));
+        }
+        (Node::try_into_payload(node_ptr).ok(), None)
+    }
+}
+
+impl<K, V, S> Iterator for IntoIter<K, V, S> {
+    type Item = (V, K);
+
+    fn next(&mut self) -> Option<(V, K)> {
+        let node_ptr = self.nodes.pop_front()?;
+        let (entry, rest) = self.split_entry(node_ptr);
+        if let Some(rest) = rest {
+            self.nodes.push_front(rest);
+        }
+        entry
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V, S> DoubleEndedIterator for IntoIter<K, V, S> {
+    fn next_back(&mut self) -> Option<(V, K)> {
+        let node_ptr = self.nodes.pop_back()?;
+        let (entry, rest) = self.split_entry(node_ptr);
+        if let Some(rest) = rest {
+            self.nodes.push_back(rest);
+        }
+        entry
+    }
+}
+
+impl<K, V, S> ExactSizeIterator for IntoIter<K, V, S> {}
+
+impl<K, V, C, S> FromIterator<(V, K)> for AvlTree<K, V, C, S>
+where
+    C: Comparator<K> + Default,
+    S: Summary<K, V>,
+{
+    fn from_iter<I: IntoIterator<Item = (V, K)>>(entries: I) -> Self {
+        let mut tree = AvlTree::default();
+        for (index, value) in entries {
+            tree.insert_after_equals(Node::new(index, value));
+        }
+        tree
+    }
+}
+
+// Goes through insert, so the tree's duplicate policy applies and entries it rejects are
+// skipped
+impl<K, V, C: Comparator<K>, S: Summary<K, V>> Extend<(V, K)> for AvlTree<K, V, C, S> {
+    fn extend<I: IntoIterator<Item = (V, K)>>(&mut self, entries: I) {
+        for (index, value) in entries {
+            let _ = self.insert(Node::new(index, value));
+        }
+    }
+}
+
+impl<'a, K, V, C, F, S> Iterator for ExtractIf<'a, K, V, C, F, S>
+where
+    S: Summary<K, V>,
+    C: Comparator<K>,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (V, K);
+
+    fn next(&mut self) -> Option<(V, K)> {
+        loop {
+            if let Some(node_ptr) = self.matched_node.take() {
+                let is_repeated = node_ptr.borrow().count > 1 && self.tree.copy_entry.is_some();
+                if is_repeated {
+                    let entry = self.tree.take_entry(Rc::clone(&node_ptr)).ok();
+                    self.matched_node = Some(node_ptr);
+                    return entry;
+                }
+                let weak = Rc::downgrade(&node_ptr);
+                match self.tree.take_entry(node_ptr) {
+                    Ok(entry) => return Some(entry),
+                    // The node stays in the tree, with the index pred may have changed
+                    Err(_) => {
+                        if let (Some(node_ptr), true) = (weak.upgrade(), has_summary::<K, V, S>()) {
+                            Node::refresh_upwards(node_ptr);
+                        }
+                    }
+                }
+            }
+            let node_ptr = self.next_node.take()?;
+            self.next_node = AvlTree::<K, V, C, S>::get_next_node(&node_ptr);
+            let is_extracted = {
+                let mut node = node_ptr.borrow_mut();
+                let node = &mut *node;
+                (self.pred)(&node.value, &mut node.index)
+            };
+            if is_extracted {
+                self.matched_node = Some(node_ptr);
+            } else if has_summary::<K, V, S>() {
+                Node::refresh_upwards(node_ptr);
+            }
+        }
+    }
+}
+
+impl<'a, K, V, S> Iterator for Range<'a, K, V, S> {
+    type Item = EntryRef<'a, K, V, S>;
+
+    fn next(&mut self) -> Option<EntryRef<'a, K, V, S>> {
+        let node_ptr = Rc::clone(self.front.as_ref()?);
+        let count = node_ptr.borrow().count;
+        self.front_taken += 1;
+        let is_back = self
+            .back
+            .as_ref()
+            .is_some_and(|back_ptr| Rc::ptr_eq(back_ptr, &node_ptr));
+        if is_back && self.front_taken + self.back_taken == count {
+            self.front = None;
+            self.back = None;
+        } else if self.front_taken == count {
+            self.front = AvlTree::<K, V, NaturalOrder, S>::get_next_node(&node_ptr);
+            self.front_taken = 0;
+        }
+        Some(EntryRef::new(node_ptr))
+    }
+}
+
+impl<'a, K, V, S> DoubleEndedIterator for Range<'a, K, V, S> {
+    fn next_back(&mut self) -> Option<EntryRef<'a, K, V, S>> {
+        let node_ptr = Rc::clone(self.back.as_ref()?);
+        let count = node_ptr.borrow().count;
+        self.back_taken += 1;
+        let is_front = self
+            .front
+            .as_ref()
+            .is_some_and(|front_ptr| Rc::ptr_eq(front_ptr, &node_ptr));
+        if is_front && self.front_taken + self.back_taken == count {
+            self.front = None;
+            self.back = None;
+        } else if self.back_taken == count {
+            self.back = AvlTree::<K, V, NaturalOrder, S>::get_previous_node(&node_ptr);
+            self.back_taken = 0;
+        }
+        Some(EntryRef::new(node_ptr))
+    }
+}
+
+impl<'a, K, V, C: Comparator<K>, S> Iterator for EqualRange<'a, K, V, C, S> {
+    type Item = NodePtr<K, V, S>;
+
+    fn next(&mut self) -> Option<NodePtr<K, V, S>> {
+        let node_ptr = self.next_node.take()?;
+        self.next_node = AvlTree::<K, V, C, S>::get_next_node(&node_ptr).filter(|next_node_ptr| {
+            self.comparator
+                .compare(self.key, &next_node_ptr.borrow().value)
+                == Ordering::Equal
+        });
+        Some(node_ptr)
+    }
+}
+
+// Summaries of a zero-sized type, such as the () of trees that don't need one, never change
+fn has_summary<K, V, S: Summary<K, V>>() -> bool {
+    std::mem::size_of::<S>() != 0
+}
+
+fn clone_entry<K: Clone, V: Clone>(index: &V, value: &K) -> (V, K) {
+    (index.clone(), value.clone())
+}
+
+fn subtree_height<K, V, S>(subtree: &Option<NodePtr<K, V, S>>) -> isize {
+    match subtree {
+        Some(node_ptr) => node_ptr.borrow().height,
+        None => 0,
+    }
+}
+
+fn subtree_size<K, V, S>(subtree: &Option<NodePtr<K, V, S>>) -> usize {
+    match subtree {
+        Some(node_ptr) => node_ptr.borrow().size,
+        None => 0,
+    }
+}
+
+#[allow(dead_code)]
+pub fn convert_node_to_vec<K: Clone, V, S>(node_ptr: NodePtr<K, V, S>) -> VecDeque<K> {
+    let mut value_vec: VecDeque<K> = VecDeque::new();
+    let mut node_vec: VecDeque<NodePtr<K, V, S>> = VecDeque::from(vec![node_ptr]);
+
+    while let Some(node_ptr) = node_vec.pop_front() {
+        value_vec.push_back(node_ptr.borrow().value.clone());
         if let Some(left_child_ptr) = node_ptr.borrow().get_left_child() {
             node_vec.push_back(left_child_ptr);
         };
@@ -317,24 +1538,21 @@ pub fn convert_node_to_vec(node_ptr: NodePtr) -> VecDeque<isize> {
     value_vec
 }
 
-pub fn build_free_from_values(values_list: Vec<isize>) -> AvlTree {
-    let mut tree = AvlTree::new();
-    for value in values_list.iter() {
-        tree.insert(Node::new(0, *value));
-    }
-    tree
+#[allow(dead_code)]
+pub fn build_free_from_values<K: Ord>(values_list: Vec<K>) -> AvlTree<K, isize> {
+    values_list.into_iter().map(|value| (0, value)).collect()
 }
 
-pub fn build_free_from_index_and_values(
-    values_list: Vec<isize>,
-    index_list: Vec<isize>,
-) -> AvlTree {
+#[allow(dead_code)]
+pub fn build_free_from_index_and_values<K: Ord, V>(
+    values_list: Vec<K>,
+    index_list: Vec<V>,
+) -> Result<AvlTree<K, V>, TreeError> {
     if values_list.len() != index_list.len() {
-        panic!("index list and value list must have the same length");
-    }
-    let mut tree = AvlTree::new();
-    for (index, value) in index_list.iter().zip(values_list.iter()) {
-        tree.insert(Node::new(*index, *value));
+        return Err(TreeError::LengthMismatch {
+            values_len: values_list.len(),
+            index_len: index_list.len(),
+        });
     }
-    tree
+    Ok(index_list.into_iter().zip(values_list).collect())
 }
@@ -2,72 +2,81 @@
 mod tests {
     use super::super::avl_node::*;
     use super::super::avl_tree::*;
+    use super::super::comparator::ReverseOrder;
+    use super::super::duplicate_policy::DuplicatePolicy;
+    use super::super::tree_error::TreeError;
     use std::cell::RefCell;
     use std::collections::VecDeque;
+    use std::ops::{Bound, RangeBounds};
     use std::rc::Rc;
 
-    fn setup_empty_tree() -> AvlTree {
-        AvlTree::new()
+    // (index, value) of an entry met while iterating
+    fn entry_of(entry: EntryRef<isize, isize>) -> (isize, isize) {
+        (*entry.index(), *entry.value())
     }
 
-    fn setup_tree1() -> AvlTree {
-        let mut tree = AvlTree::new();
+    fn setup_empty_tree() -> IsizeAvlTree {
+        IsizeAvlTree::new()
+    }
+
+    fn setup_tree1() -> IsizeAvlTree {
+        let mut tree = IsizeAvlTree::new();
         let node = setup_node1();
-        tree.insert(node);
+        tree.insert(node).unwrap();
         tree
     }
 
-    fn setup_node1() -> Node {
+    fn setup_node1() -> IsizeNode {
         Node::new(10, 20)
     }
 
-    fn setup_tree2() -> AvlTree {
-        let mut tree = AvlTree::new();
+    fn setup_tree2() -> IsizeAvlTree {
+        let mut tree = IsizeAvlTree::new();
         let node1 = setup_node1();
         let node2 = Node::new(10, 10);
         let node3 = Node::new(10, 30);
         let node4 = Node::new(10, 12);
-        tree.insert(node1);
-        tree.insert(node2);
-        tree.insert(node3);
-        tree.insert(node4);
+        tree.insert(node1).unwrap();
+        tree.insert(node2).unwrap();
+        tree.insert(node3).unwrap();
+        tree.insert(node4).unwrap();
         tree
     }
 
-    fn setup_tree3() -> AvlTree {
-        let mut tree = AvlTree::new();
+    fn setup_tree3() -> IsizeAvlTree {
+        let mut tree = IsizeAvlTree::new();
         let node1 = setup_node1();
         let node2 = Node::new(10, 30);
         let node3 = Node::new(10, 40);
-        tree.insert(node1);
-        tree.insert(node2);
-        tree.insert(node3);
+        tree.insert(node1).unwrap();
+        tree.insert(node2).unwrap();
+        tree.insert(node3).unwrap();
         tree
     }
 
-    fn setup_tree4() -> AvlTree {
-        let mut tree = AvlTree::new();
+    fn setup_tree4() -> IsizeAvlTree {
+        let mut tree = IsizeAvlTree::new();
         let node1 = setup_node1();
         let node2 = Node::new(10, 10);
         let node3 = Node::new(10, 5);
-        tree.insert(node1);
-        tree.insert(node2);
-        tree.insert(node3);
+        tree.insert(node1).unwrap();
+        tree.insert(node2).unwrap();
+        tree.insert(node3).unwrap();
         tree
     }
 
-    fn setup_tree5() -> AvlTree {
-        let mut tree = AvlTree::new();
+    fn setup_tree5() -> IsizeAvlTree {
+        let mut tree = IsizeAvlTree::new();
         let node1 = setup_node1();
         let node2 = Node::new(10, 10);
-        tree.insert(node1);
-        tree.insert(node2);
+        tree.insert(node1).unwrap();
+        tree.insert(node2).unwrap();
         tree
     }
 
     #[test]
     fn test_tree3_left_rotate() {
-        let mut tree = setup_tree3();
+        let tree = setup_tree3();
         // tree.left_rotate(Rc::clone(&tree.root.as_ref().unwrap()));
         let result = convert_node_to_vec(tree.root.unwrap());
         assert_eq!(result, VecDeque::from(vec![30, 20, 40]));
@@ -75,7 +84,7 @@ mod tests {
 
     #[test]
     fn test_tree4_right_rotate() {
-        let mut tree = setup_tree4();
+        let tree = setup_tree4();
         // tree.right_rotate(Rc::clone(&tree.root.as_ref().unwrap()));
         let result = convert_node_to_vec(tree.root.unwrap());
         assert_eq!(result, VecDeque::from(vec![10, 5, 20]));
@@ -99,7 +108,7 @@ mod tests {
     fn test_empty_tree_insert() {
         let mut tree = setup_empty_tree();
         let node = setup_node1();
-        tree.insert(node);
+        tree.insert(node).unwrap();
         assert_eq!(tree.root, Some(Rc::new(RefCell::new(setup_node1()))));
     }
 
@@ -107,16 +116,19 @@ mod tests {
     fn test_tree1_insert1() {
         let mut tree = setup_tree1();
         let node = Node::new(10, 10);
-        tree.insert(node);
+        tree.insert(node).unwrap();
         let left_child = tree.root.as_ref().unwrap().borrow().get_left_child();
-        assert_eq!(left_child, Some(Rc::new(RefCell::new(Node::new(10, 10)))));
+        assert_eq!(
+            left_child,
+            Some(Rc::new(RefCell::new(IsizeNode::new(10, 10))))
+        );
     }
 
     #[test]
     fn test_tree1_insert1_parent() {
         let mut tree = setup_tree1();
         let node = Node::new(10, 10);
-        tree.insert(node);
+        tree.insert(node).unwrap();
         let left_child = tree.root.as_ref().unwrap().borrow().get_left_child();
         let parent = left_child.unwrap().borrow().parent.upgrade();
         assert_eq!(parent, tree.root);
@@ -126,7 +138,7 @@ mod tests {
     fn test_tree1_insert1_bf() {
         let mut tree = setup_tree1();
         let node = Node::new(10, 10);
-        tree.insert(node);
+        tree.insert(node).unwrap();
         assert_eq!(tree.root.unwrap().borrow().get_balance_factor(), -1);
     }
 
@@ -134,7 +146,7 @@ mod tests {
     fn test_tree1_insert1_height() {
         let mut tree = setup_tree1();
         let node = Node::new(10, 10);
-        tree.insert(node);
+        tree.insert(node).unwrap();
         assert_eq!(tree.root.unwrap().borrow().height, 2);
     }
 
@@ -143,8 +155,8 @@ mod tests {
         let mut tree = setup_tree1();
         let node1 = Node::new(10, 10);
         let node2 = Node::new(10, 5);
-        tree.insert(node1);
-        tree.insert(node2);
+        tree.insert(node1).unwrap();
+        tree.insert(node2).unwrap();
         // let left_child_1 = tree.root.as_ref().unwrap().borrow().get_left_child();
         // let left_child_2 = left_child_1.as_ref().unwrap().borrow().get_left_child();
         // assert_eq!(left_child_2, Some(Rc::new(RefCell::new(Node::new(10, 5)))));
@@ -157,8 +169,8 @@ mod tests {
         let mut tree = setup_tree1();
         let node1 = Node::new(10, 10);
         let node2 = Node::new(10, 5);
-        tree.insert(node1);
-        tree.insert(node2);
+        tree.insert(node1).unwrap();
+        tree.insert(node2).unwrap();
         assert_eq!(tree.root.unwrap().borrow().get_balance_factor(), 0);
     }
 
@@ -168,9 +180,9 @@ mod tests {
         let node1 = Node::new(10, 10);
         let node2 = Node::new(10, 5);
         let node3 = Node::new(10, 12);
-        tree.insert(node1);
-        tree.insert(node2);
-        tree.insert(node3);
+        tree.insert(node1).unwrap();
+        tree.insert(node2).unwrap();
+        tree.insert(node3).unwrap();
 
         // let left_child_1 = tree.root.as_ref().unwrap().borrow().get_left_child();
         // let right_child_2 = left_child_1.as_ref().unwrap().borrow().get_right_child();
@@ -185,34 +197,34 @@ mod tests {
     #[test]
     fn test_empty_tree_search() {
         let tree = setup_empty_tree();
-        assert_eq!(tree.search_tree_by_value(19), None);
+        assert_eq!(tree.search_tree_by_value(&19), None);
     }
 
     #[test]
     fn test_tree2_search() {
         let tree = setup_tree2();
-        assert_eq!(tree.search_tree_by_value(30).unwrap().borrow().value, 30);
+        assert_eq!(tree.search_tree_by_value(&30).unwrap().borrow().value, 30);
     }
 
     #[test]
     fn test_tree2_max() {
         let tree = setup_tree2();
-        let node_ptr = AvlTree::get_maximum_child(tree.root.unwrap());
+        let node_ptr = IsizeAvlTree::get_maximum_child(tree.root.unwrap());
         assert_eq!(node_ptr.borrow().value, 30);
     }
 
     #[test]
     fn test_tree2_min() {
         let tree = setup_tree2();
-        let node_ptr = AvlTree::get_minimum_child(tree.root.unwrap());
+        let node_ptr = IsizeAvlTree::get_minimum_child(tree.root.unwrap());
         assert_eq!(node_ptr.borrow().value, 10);
     }
 
     #[test]
     fn test_tree5_delete() {
         let mut tree = setup_tree5();
-        let node = tree.search_tree_by_value(10);
-        tree.delete_node(node.unwrap());
+        let node = tree.search_tree_by_value(&10);
+        tree.delete_node(node.unwrap()).unwrap();
         assert_eq!(tree.root.as_ref().unwrap().borrow().value, 20);
         assert_eq!(tree.root.unwrap().borrow().parent.upgrade(), None);
     }
@@ -220,7 +232,7 @@ mod tests {
     #[test]
     fn test_left_right_rotate() {
         let mut tree = build_free_from_values(vec![30, 40]);
-        tree.insert(Node::new(0, 35));
+        tree.insert(Node::new(0, 35)).unwrap();
         let root = tree.root.as_ref().unwrap();
         let root_copy = Rc::clone(tree.root.as_ref().unwrap());
         assert_eq!(
@@ -288,7 +300,7 @@ mod tests {
     #[test]
     fn test_right_left_rotate() {
         let mut tree = build_free_from_values(vec![30, 20]);
-        tree.insert(Node::new(0, 25));
+        tree.insert(Node::new(0, 25)).unwrap();
         let root = Rc::clone(tree.root.as_ref().unwrap());
         let node_20 = root.borrow().get_left_child().unwrap();
         let node_30 = root.borrow().get_right_child().unwrap();
@@ -308,7 +320,7 @@ mod tests {
 
     #[test]
     fn test_left_rotate() {
-        let mut tree = build_free_from_values(vec![30, 20, 10]);
+        let tree = build_free_from_values(vec![30, 20, 10]);
         let root = Rc::clone(tree.root.as_ref().unwrap());
         let node_10 = root.borrow().get_left_child().unwrap();
         let node_30 = root.borrow().get_right_child().unwrap();
@@ -328,7 +340,7 @@ mod tests {
 
     #[test]
     fn test_left_rotate2() {
-        let mut tree = build_free_from_values(vec![15, 10, 20, 30, 40]);
+        let tree = build_free_from_values(vec![15, 10, 20, 30, 40]);
         let root = Rc::clone(tree.root.as_ref().unwrap());
         let node_10 = root.borrow().get_left_child().unwrap();
         let node_30 = root.borrow().get_right_child().unwrap();
@@ -348,7 +360,7 @@ mod tests {
 
     #[test]
     fn test_right_rotate() {
-        let mut tree = build_free_from_values(vec![30, 40, 50]);
+        let tree = build_free_from_values(vec![30, 40, 50]);
         let root = Rc::clone(tree.root.as_ref().unwrap());
         let node_30 = root.borrow().get_left_child().unwrap();
         let node_50 = root.borrow().get_right_child().unwrap();
@@ -366,19 +378,19 @@ mod tests {
         );
     }
 
-    fn setup_tree6() -> AvlTree {
+    fn setup_tree6() -> IsizeAvlTree {
         build_free_from_values(vec![30, 50, 70, 65, 68])
     }
 
     #[test]
     fn test_tree6_root_correct() {
-        let mut tree = setup_tree6();
+        let tree = setup_tree6();
         assert_eq!(tree.root.unwrap().borrow().value, 50);
     }
 
     #[test]
     fn test_tree6_init_correct() {
-        let mut tree = setup_tree6();
+        let tree = setup_tree6();
         let root = Rc::clone(tree.root.as_ref().unwrap());
         let node_30 = root.borrow().get_left_child().unwrap();
         let node_68 = root.borrow().get_right_child().unwrap();
@@ -399,7 +411,7 @@ mod tests {
     #[test]
     fn test_tree6_insertion_correct() {
         let mut tree = setup_tree6();
-        tree.insert(Node::new(0, 75));
+        tree.insert(Node::new(0, 75)).unwrap();
         assert_eq!(
             convert_node_to_vec(tree.root.unwrap()),
             VecDeque::from(vec![68, 50, 70, 30, 65, 75])
@@ -409,7 +421,7 @@ mod tests {
     #[test]
     fn test_tree6_insertion_correct2() {
         let mut tree = setup_tree6();
-        tree.insert(Node::new(0, 64));
+        tree.insert(Node::new(0, 64)).unwrap();
         assert_eq!(
             convert_node_to_vec(tree.root.unwrap()),
             VecDeque::from(vec![65, 50, 68, 30, 64, 70])
@@ -419,16 +431,16 @@ mod tests {
     #[test]
     fn test_tree_min_child() {
         let tree = setup_tree6();
-        let min_child = AvlTree::get_minimum_child(tree.root.unwrap());
+        let min_child = IsizeAvlTree::get_minimum_child(tree.root.unwrap());
         assert_eq!(min_child.borrow().value, 30);
     }
 
     #[test]
     fn test_tree6_deletion_correct() {
         let mut tree = setup_tree6();
-        let node_found = tree.search_tree_by_value(30);
+        let node_found = tree.search_tree_by_value(&30);
         assert_eq!(node_found.as_ref().unwrap().borrow().value, 30);
-        tree.delete_node(node_found.unwrap());
+        tree.delete_node(node_found.unwrap()).unwrap();
         assert_eq!(
             convert_node_to_vec(tree.root.unwrap()),
             VecDeque::from(vec![68, 50, 70, 65])
@@ -438,8 +450,8 @@ mod tests {
     #[test]
     fn test_tree6_deletion_correct2() {
         let mut tree = setup_tree6();
-        let node_found = tree.search_tree_by_value(70);
-        tree.delete_node(node_found.unwrap());
+        let node_found = tree.search_tree_by_value(&70);
+        tree.delete_node(node_found.unwrap()).unwrap();
         assert_eq!(
             convert_node_to_vec(tree.root.unwrap()),
             VecDeque::from(vec![50, 30, 68, 65])
@@ -449,9 +461,9 @@ mod tests {
     #[test]
     fn test_tree6_deletion_correct3() {
         let mut tree = setup_tree6();
-        let node_found = tree.search_tree_by_value(50);
+        let node_found = tree.search_tree_by_value(&50);
         assert_eq!(node_found.as_ref().unwrap().borrow().value, 50);
-        tree.delete_node(node_found.unwrap());
+        tree.delete_node(node_found.unwrap()).unwrap();
         assert_eq!(
             convert_node_to_vec(tree.root.unwrap()),
             VecDeque::from(vec![65, 30, 68, 70])
@@ -461,18 +473,18 @@ mod tests {
     #[test]
     fn test_delete_root_with_no_child() {
         let mut tree = build_free_from_values(vec![10]);
-        let node_found = tree.search_tree_by_value(10);
+        let node_found = tree.search_tree_by_value(&10);
         assert_eq!(node_found.as_ref().unwrap().borrow().value, 10);
-        tree.delete_node(node_found.unwrap());
+        tree.delete_node(node_found.unwrap()).unwrap();
         assert_eq!(tree.root, None);
     }
 
     #[test]
     fn test_delete_root_with_both_child() {
         let mut tree = build_free_from_values(vec![10, 5, 15]);
-        let node_found = tree.search_tree_by_value(10);
+        let node_found = tree.search_tree_by_value(&10);
         assert_eq!(node_found.as_ref().unwrap().borrow().value, 10);
-        tree.delete_node(node_found.unwrap());
+        tree.delete_node(node_found.unwrap()).unwrap();
         assert_eq!(
             convert_node_to_vec(tree.root.unwrap()),
             VecDeque::from(vec![15, 5])
@@ -482,9 +494,9 @@ mod tests {
     #[test]
     fn test_delete_root_with_one_child() {
         let mut tree = build_free_from_values(vec![10, 5]);
-        let node_found = tree.search_tree_by_value(10);
+        let node_found = tree.search_tree_by_value(&10);
         assert_eq!(node_found.as_ref().unwrap().borrow().value, 10);
-        tree.delete_node(node_found.unwrap());
+        tree.delete_node(node_found.unwrap()).unwrap();
         assert_eq!(
             convert_node_to_vec(tree.root.unwrap()),
             VecDeque::from(vec![5])
@@ -494,11 +506,1167 @@ mod tests {
     #[test]
     fn test_delete_leaf() {
         let mut tree = build_free_from_values(vec![10, 5, 15]);
-        let node_found = tree.search_tree_by_value(5);
-        tree.delete_node(node_found.unwrap());
+        let node_found = tree.search_tree_by_value(&5);
+        tree.delete_node(node_found.unwrap()).unwrap();
         assert_eq!(
             convert_node_to_vec(tree.root.unwrap()),
             VecDeque::from(vec![10, 15])
         );
     }
+
+    #[test]
+    fn test_string_keys() {
+        let mut tree: AvlTree<String, &str> = AvlTree::new();
+        for word in ["pear", "apple", "fig", "cherry"] {
+            tree.insert(Node::new(word, word.to_uppercase())).unwrap();
+        }
+        assert_eq!(
+            convert_node_to_vec(Rc::clone(tree.root.as_ref().unwrap())),
+            VecDeque::from(vec![
+                "FIG".to_string(),
+                "APPLE".to_string(),
+                "PEAR".to_string(),
+                "CHERRY".to_string()
+            ])
+        );
+        let node_found = tree.search_tree_by_value(&"FIG".to_string());
+        assert_eq!(node_found.unwrap().borrow().index, "fig");
+        tree.delete_node(tree.search_tree_by_value(&"CHERRY".to_string()).unwrap())
+            .unwrap();
+        assert_eq!(
+            convert_node_to_vec(tree.root.unwrap()),
+            VecDeque::from(vec![
+                "FIG".to_string(),
+                "APPLE".to_string(),
+                "PEAR".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_struct_payload() {
+        #[derive(Debug, PartialEq)]
+        struct Event {
+            name: String,
+        }
+        let mut tree: AvlTree<u64, Event> = AvlTree::new();
+        for (timestamp, name) in [(30, "c"), (10, "a"), (20, "b")] {
+            let event = Event {
+                name: name.to_string(),
+            };
+            tree.insert(Node::new(event, timestamp)).unwrap();
+        }
+        let node_found = tree.search_tree_by_value(&20).unwrap();
+        assert_eq!(node_found.borrow().index.name, "b");
+        assert_eq!(
+            convert_node_to_vec(tree.root.unwrap()),
+            VecDeque::from(vec![20, 10, 30])
+        );
+    }
+
+    #[test]
+    fn test_remove_by_key() {
+        let mut tree = setup_tree6();
+        tree.search_tree_by_value(&50).unwrap().borrow_mut().index = 5;
+        assert_eq!(tree.remove(&50), Some((5, 50)));
+        assert_eq!(
+            convert_node_to_vec(Rc::clone(tree.root.as_ref().unwrap())),
+            VecDeque::from(vec![65, 30, 68, 70])
+        );
+        assert_eq!(tree.search_tree_by_value(&50), None);
+    }
+
+    #[test]
+    fn test_remove_missing_key() {
+        let mut tree = setup_tree6();
+        assert_eq!(tree.remove(&51), None);
+        assert_eq!(
+            convert_node_to_vec(tree.root.unwrap()),
+            VecDeque::from(vec![50, 30, 68, 65, 70])
+        );
+    }
+
+    #[test]
+    fn test_remove_on_empty_tree() {
+        let mut tree = setup_empty_tree();
+        assert_eq!(tree.remove(&1), None);
+        assert_eq!(tree.remove_min(), None);
+        assert_eq!(tree.remove_max(), None);
+        assert_eq!(tree.root, None);
+    }
+
+    #[test]
+    fn test_remove_while_handle_is_held() {
+        let mut tree = setup_tree6();
+        let handle = tree.find_handle(&30).unwrap();
+        assert_eq!(tree.remove(&30), Some((0, 30)));
+        assert_eq!(tree.resolve(&handle).err(), Some(TreeError::StaleHandle));
+        assert_eq!(tree.search_tree_by_value(&30), None);
+    }
+
+    #[test]
+    fn test_remove_while_node_ptr_is_held() {
+        let mut tree = setup_tree6();
+        let len = tree.len();
+        let root_found = tree.search_tree_by_value(&50).unwrap();
+        assert_eq!(tree.remove(&50), None);
+        assert_eq!(tree.len(), len);
+        assert_eq!(tree.pop_first(), Some((0, 30)));
+        assert_eq!(root_found.borrow().value, 50);
+        drop(root_found);
+        assert_eq!(tree.remove(&50), Some((0, 50)));
+        assert_eq!(tree.len(), len - 2);
+    }
+
+    #[test]
+    fn test_remove_without_clone() {
+        let mut tree: AvlTree<isize, Task> = AvlTree::new();
+        for (priority, name) in [(2, "write"), (1, "read"), (3, "send"), (4, "close")] {
+            tree.insert(Node::new(Task(name.to_string()), priority))
+                .unwrap();
+        }
+        assert_eq!(tree.remove_min(), Some((Task("read".to_string()), 1)));
+        assert_eq!(tree.remove_max(), Some((Task("close".to_string()), 4)));
+        assert_eq!(tree.remove(&3), Some((Task("send".to_string()), 3)));
+        assert_eq!(tree.remove(&3), None);
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn test_pop_without_clone() {
+        let mut tree: AvlTree<isize, Task> = AvlTree::new();
+        for (priority, name) in [(2, "write"), (1, "read"), (3, "send")] {
+            tree.insert(Node::new(Task(name.to_string()), priority))
+                .unwrap();
+        }
+        assert_eq!(tree.pop_first(), Some((Task("read".to_string()), 1)));
+        assert_eq!(tree.pop_last(), Some((Task("send".to_string()), 3)));
+        assert_eq!(*tree.first().unwrap().index(), Task("write".to_string()));
+        assert_eq!(*tree.last().unwrap().index(), Task("write".to_string()));
+        assert_eq!(tree.pop_first(), Some((Task("write".to_string()), 2)));
+        assert!(tree.pop_last().is_none());
+    }
+
+    #[test]
+    fn test_remove_min_and_max() {
+        let mut tree = setup_tree6();
+        assert_eq!(tree.remove_min(), Some((0, 30)));
+        assert_eq!(tree.remove_max(), Some((0, 70)));
+        assert_eq!(
+            convert_node_to_vec(Rc::clone(tree.root.as_ref().unwrap())),
+            VecDeque::from(vec![65, 50, 68])
+        );
+        assert_eq!(tree.remove_min(), Some((0, 50)));
+        assert_eq!(tree.remove_min(), Some((0, 65)));
+        assert_eq!(tree.remove_max(), Some((0, 68)));
+        assert_eq!(tree.remove_max(), None);
+        assert_eq!(tree.root, None);
+    }
+
+    #[test]
+    fn test_build_with_length_mismatch() {
+        let result = build_free_from_index_and_values(vec![1, 2, 3], vec![10, 20]);
+        assert_eq!(
+            result.unwrap_err(),
+            TreeError::LengthMismatch {
+                values_len: 3,
+                index_len: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_build_from_index_and_values() {
+        let tree = build_free_from_index_and_values(vec![1, 2, 3], vec![10, 20, 30]).unwrap();
+        assert_eq!(tree.search_tree_by_value(&2).unwrap().borrow().index, 20);
+    }
+
+    #[test]
+    fn test_delete_node_on_empty_tree() {
+        let mut tree = setup_empty_tree();
+        let node = Rc::new(RefCell::new(setup_node1()));
+        assert_eq!(tree.delete_node(node), Err(TreeError::EmptyTree));
+    }
+
+    #[test]
+    fn test_delete_foreign_node() {
+        let mut tree = setup_tree6();
+        let other_tree = setup_tree6();
+        let foreign_node = other_tree.search_tree_by_value(&65).unwrap();
+        assert_eq!(tree.delete_node(foreign_node), Err(TreeError::ForeignNode));
+        assert_eq!(
+            convert_node_to_vec(tree.root.unwrap()),
+            VecDeque::from(vec![50, 30, 68, 65, 70])
+        );
+    }
+
+    #[test]
+    fn test_delete_stale_node() {
+        let mut tree = setup_tree6();
+        let node_found = tree.search_tree_by_value(&30).unwrap();
+        tree.delete_node(Rc::clone(&node_found)).unwrap();
+        assert_eq!(tree.delete_node(node_found), Err(TreeError::StaleHandle));
+        assert_eq!(
+            convert_node_to_vec(tree.root.unwrap()),
+            VecDeque::from(vec![68, 50, 70, 65])
+        );
+    }
+
+    #[test]
+    fn test_rotate_missing_child() {
+        let mut tree = setup_tree6();
+        let leaf = tree.search_tree_by_value(&30).unwrap();
+        assert_eq!(
+            tree.left_rotate(Rc::clone(&leaf)),
+            Err(TreeError::MissingChild)
+        );
+        assert_eq!(tree.right_rotate(leaf), Err(TreeError::MissingChild));
+    }
+
+    #[test]
+    fn test_rotate_foreign_node() {
+        let mut tree = setup_tree6();
+        let other_tree = setup_tree6();
+        let foreign_root = Rc::clone(other_tree.root.as_ref().unwrap());
+        assert_eq!(tree.left_rotate(foreign_root), Err(TreeError::ForeignNode));
+    }
+
+    #[test]
+    fn test_explicit_left_rotate() {
+        let mut tree = setup_tree6();
+        let root = Rc::clone(tree.root.as_ref().unwrap());
+        tree.left_rotate(root).unwrap();
+        assert_eq!(
+            convert_node_to_vec(tree.root.unwrap()),
+            VecDeque::from(vec![68, 50, 70, 30, 65])
+        );
+    }
+
+    #[test]
+    fn test_tree_error_display() {
+        assert_eq!(TreeError::EmptyTree.to_string(), "the tree is empty");
+    }
+
+    // Checks parent links, heights, balance and ordering below node_ptr, returning its size
+    fn assert_avl_invariants(node_ptr: &IsizeNodePtr) -> usize {
+        let node = node_ptr.borrow();
+        let mut size = 1;
+        for (child_ptr_option, is_left) in [(&node.left_child, true), (&node.right_child, false)] {
+            if let Some(child_ptr) = child_ptr_option {
+                let child_parent = child_ptr.borrow().parent.upgrade().unwrap();
+                assert!(Rc::ptr_eq(&child_parent, node_ptr));
+                assert_eq!(child_ptr.borrow().is_left_child(), is_left);
+                if is_left {
+                    assert!(child_ptr.borrow().value <= node.value);
+                } else {
+                    assert!(child_ptr.borrow().value >= node.value);
+                }
+                size += assert_avl_invariants(child_ptr);
+            }
+        }
+        let mut expected = IsizeNode::new(0, 0);
+        expected.left_child = node.get_left_child();
+        expected.right_child = node.get_right_child();
+        expected.set_height();
+        assert_eq!(node.height, expected.height);
+        assert_eq!(
+            node.size,
+            node.get_left_child_size() + node.count + node.get_right_child_size()
+        );
+        assert!(node.get_balance_factor().abs() <= 1);
+        size
+    }
+
+    #[test]
+    fn test_insert_and_delete_many_duplicates() {
+        let mut tree = setup_empty_tree();
+        for index in 0..200 {
+            tree.insert(Node::new(0, index % 3)).unwrap();
+            assert_avl_invariants(tree.root.as_ref().unwrap());
+        }
+        for remaining in (0..200usize).rev() {
+            let node_found = tree
+                .search_tree_by_value(&(remaining as isize % 3))
+                .unwrap();
+            tree.delete_node(node_found).unwrap();
+            match &tree.root {
+                Some(root) => assert_eq!(assert_avl_invariants(root), remaining),
+                None => assert_eq!(remaining, 0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_delete_duplicate_leaf_keeps_equal_sibling() {
+        let mut tree = build_free_from_values(vec![5, 5, 5]);
+        let root = Rc::clone(tree.root.as_ref().unwrap());
+        let left_leaf = root.borrow().get_left_child().unwrap();
+        let right_leaf = root.borrow().get_right_child().unwrap();
+        tree.delete_node(Rc::clone(&right_leaf)).unwrap();
+        assert!(root.borrow().right_child.is_none());
+        assert!(Rc::ptr_eq(
+            &root.borrow().get_left_child().unwrap(),
+            &left_leaf
+        ));
+    }
+
+    #[test]
+    fn test_delete_root_among_duplicates() {
+        let mut tree = build_free_from_values(vec![5, 5, 5, 5, 5, 5, 5]);
+        for remaining in (0..7).rev() {
+            let root = Rc::clone(tree.root.as_ref().unwrap());
+            tree.delete_node(root).unwrap();
+            match &tree.root {
+                Some(root) => assert_eq!(assert_avl_invariants(root), remaining),
+                None => assert_eq!(remaining, 0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_delete_rebalances_when_parent_height_is_unchanged() {
+        let mut tree = setup_empty_tree();
+        for index in 0..200 {
+            tree.insert(Node::new(0, (index * 37) % 200)).unwrap();
+        }
+        for removed in 0..200usize {
+            let node_found = tree.search_tree_by_value(&(removed as isize * 91 % 200));
+            tree.delete_node(node_found.unwrap()).unwrap();
+            if let Some(root) = &tree.root {
+                assert_eq!(assert_avl_invariants(root), 199 - removed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_tree_len_and_height() {
+        let tree = setup_empty_tree();
+        assert_eq!(tree.len(), 0);
+        assert!(tree.is_empty());
+        assert_eq!(tree.height(), 0);
+    }
+
+    #[test]
+    fn test_len_and_height_after_insert() {
+        let tree = setup_tree6();
+        assert_eq!(tree.len(), 5);
+        assert!(!tree.is_empty());
+        assert_eq!(tree.height(), 3);
+    }
+
+    #[test]
+    fn test_len_after_delete() {
+        let mut tree = setup_tree6();
+        let node_found = tree.search_tree_by_value(&50).unwrap();
+        tree.delete_node(node_found).unwrap();
+        assert_eq!(tree.len(), 4);
+        tree.remove(&30);
+        tree.remove(&31);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.height(), 2);
+    }
+
+    #[test]
+    fn test_len_after_delete_root() {
+        let mut tree = build_free_from_values(vec![10]);
+        let root = Rc::clone(tree.root.as_ref().unwrap());
+        tree.delete_node(root).unwrap();
+        assert_eq!(tree.len(), 0);
+        assert!(tree.is_empty());
+        assert_eq!(tree.height(), 0);
+    }
+
+    #[test]
+    fn test_len_matches_node_count() {
+        let mut tree = setup_empty_tree();
+        for index in 0..100 {
+            tree.insert(Node::new(0, (index * 37) % 50)).unwrap();
+            assert_eq!(
+                tree.len(),
+                assert_avl_invariants(tree.root.as_ref().unwrap())
+            );
+        }
+        for index in 0..60 {
+            tree.remove(&(index % 50));
+            assert_eq!(
+                tree.len(),
+                assert_avl_invariants(tree.root.as_ref().unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn test_len_counts_duplicates_under_count_policy() {
+        let mut tree = IsizeAvlTree::new().with_duplicate_policy(DuplicatePolicy::Count);
+        for value in [1, 2, 2, 2, 3] {
+            tree.insert(Node::new(0, value)).unwrap();
+        }
+        assert_eq!(tree.len(), 5);
+        tree.remove(&2);
+        assert_eq!(tree.len(), 4);
+        tree.remove(&1);
+        assert_eq!(tree.len(), 3);
+        let node_found = tree.search_tree_by_value(&2).unwrap();
+        tree.delete_node(node_found).unwrap();
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut tree = setup_tree6();
+        tree.clear();
+        assert_eq!(tree.len(), 0);
+        assert_eq!(tree.height(), 0);
+        assert_eq!(tree.root, None);
+        tree.insert(Node::new(0, 1)).unwrap();
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn test_iter_in_order() {
+        let tree = build_free_from_values(vec![40, 10, 30, 20, 50, 25]);
+        let values: Vec<isize> = tree.iter().map(|entry| *entry.value()).collect();
+        assert_eq!(values, vec![10, 20, 25, 30, 40, 50]);
+    }
+
+    #[test]
+    fn test_iter_rev() {
+        let tree = build_free_from_values(vec![40, 10, 30, 20, 50, 25]);
+        let values: Vec<isize> = tree.iter().rev().map(|entry| *entry.value()).collect();
+        assert_eq!(values, vec![50, 40, 30, 25, 20, 10]);
+    }
+
+    #[test]
+    fn test_iter_from_both_ends() {
+        let tree = build_free_from_values((1..=5).collect());
+        let mut iter = tree.iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next().map(entry_of), Some((0, 1)));
+        assert_eq!(iter.next_back().map(entry_of), Some((0, 5)));
+        assert_eq!(iter.next().map(entry_of), Some((0, 2)));
+        assert_eq!(iter.next_back().map(entry_of), Some((0, 4)));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next_back().map(entry_of), Some((0, 3)));
+        assert_eq!(iter.next().map(entry_of), None);
+        assert_eq!(iter.next_back().map(entry_of), None);
+    }
+
+    #[test]
+    fn test_iter_empty_tree() {
+        let tree = setup_empty_tree();
+        assert_eq!(tree.iter().len(), 0);
+        assert!(tree.iter().next().is_none());
+        assert!(tree.iter().next_back().is_none());
+    }
+
+    #[test]
+    fn test_iter_keeps_insertion_order_of_duplicates() {
+        let mut tree = setup_empty_tree();
+        for (index, value) in [(1, 5), (2, 3), (3, 5), (4, 5)] {
+            tree.insert(Node::new(index, value)).unwrap();
+        }
+        let entries: Vec<(isize, isize)> = (&tree).into_iter().map(entry_of).collect();
+        assert_eq!(entries, vec![(2, 3), (1, 5), (3, 5), (4, 5)]);
+    }
+
+    // A payload that can't be cloned, like a task handed over to a queue
+    #[derive(Debug, PartialEq, Eq)]
+    struct Task(String);
+
+    #[test]
+    fn test_iter_borrows_payloads() {
+        let mut tree: AvlTree<isize, Task> = AvlTree::new();
+        for (priority, name) in [(2, "write"), (1, "read"), (3, "send")] {
+            tree.insert(Node::new(Task(name.to_string()), priority))
+                .unwrap();
+        }
+        let names: Vec<String> = tree.iter().map(|entry| entry.index().0.clone()).collect();
+        assert_eq!(names, vec!["read", "write", "send"]);
+        let first = tree.iter().next().unwrap();
+        assert_eq!(*first.index(), Task("read".to_string()));
+    }
+
+    #[test]
+    fn test_iter_repeats_counted_entries() {
+        let mut tree = IsizeAvlTree::new().with_duplicate_policy(DuplicatePolicy::Count);
+        for value in [2, 1, 2, 3, 2] {
+            tree.insert(Node::new(0, value)).unwrap();
+        }
+        let mut iter = tree.iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next_back().map(entry_of), Some((0, 3)));
+        assert_eq!(iter.next_back().map(entry_of), Some((0, 2)));
+        let values: Vec<isize> = iter.map(|entry| *entry.value()).collect();
+        assert_eq!(values, vec![1, 2, 2]);
+    }
+
+    #[test]
+    fn test_get_previous_node() {
+        let tree = build_free_from_values((1..=20).collect());
+        let mut node_ptr = IsizeAvlTree::get_maximum_child(Rc::clone(tree.root.as_ref().unwrap()));
+        for expected in (1..20).rev() {
+            node_ptr = IsizeAvlTree::get_previous_node(&node_ptr).unwrap();
+            assert_eq!(node_ptr.borrow().value, expected);
+        }
+        assert!(IsizeAvlTree::get_previous_node(&node_ptr).is_none());
+    }
+
+    fn range_values<R: RangeBounds<isize>>(tree: &IsizeAvlTree, bounds: R) -> Vec<isize> {
+        tree.range(bounds).map(|entry| *entry.value()).collect()
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let tree = build_free_from_values((1..=10).map(|value| value * 10).collect());
+        assert_eq!(range_values(&tree, 30..60), vec![30, 40, 50]);
+        assert_eq!(range_values(&tree, 30..=60), vec![30, 40, 50, 60]);
+        assert_eq!(range_values(&tree, 25..55), vec![30, 40, 50]);
+        assert_eq!(range_values(&tree, ..25), vec![10, 20]);
+        assert_eq!(range_values(&tree, 85..), vec![90, 100]);
+        assert_eq!(range_values(&tree, ..).len(), 10);
+        assert_eq!(
+            range_values(&tree, (Bound::Excluded(30), Bound::Included(50))),
+            vec![40, 50]
+        );
+    }
+
+    #[test]
+    fn test_range_empty() {
+        let tree = build_free_from_values(vec![10, 20, 30]);
+        assert!(range_values(&tree, 11..20).is_empty());
+        assert!(range_values(&tree, 31..).is_empty());
+        assert!(range_values(&tree, ..10).is_empty());
+        assert!(range_values(&tree, 20..20).is_empty());
+        assert!(range_values(&setup_empty_tree(), ..).is_empty());
+    }
+
+    #[test]
+    fn test_range_rev_and_both_ends() {
+        let tree = build_free_from_values((1..=20).collect());
+        let values: Vec<isize> = tree
+            .range(5..=9)
+            .rev()
+            .map(|entry| *entry.value())
+            .collect();
+        assert_eq!(values, vec![9, 8, 7, 6, 5]);
+        let mut range = tree.range(5..8);
+        assert_eq!(range.next_back().map(entry_of), Some((0, 7)));
+        assert_eq!(range.next().map(entry_of), Some((0, 5)));
+        assert_eq!(range.next_back().map(entry_of), Some((0, 6)));
+        assert_eq!(range.next().map(entry_of), None);
+        assert_eq!(range.next_back().map(entry_of), None);
+    }
+
+    #[test]
+    fn test_range_includes_all_duplicates() {
+        let mut tree = setup_empty_tree();
+        for (index, value) in [(1, 2), (2, 1), (3, 2), (4, 3), (5, 2)] {
+            tree.insert(Node::new(index, value)).unwrap();
+        }
+        let entries: Vec<(isize, isize)> = tree.range(2..=2).map(entry_of).collect();
+        assert_eq!(entries, vec![(1, 2), (3, 2), (5, 2)]);
+    }
+
+    #[test]
+    fn test_range_repeats_counted_entries() {
+        let mut tree = IsizeAvlTree::new().with_duplicate_policy(DuplicatePolicy::Count);
+        for value in [1, 2, 2, 2, 3] {
+            tree.insert(Node::new(0, value)).unwrap();
+        }
+        let mut range = tree.range(2..3);
+        assert_eq!(range.next().map(entry_of), Some((0, 2)));
+        assert_eq!(range.next_back().map(entry_of), Some((0, 2)));
+        assert_eq!(range.next().map(entry_of), Some((0, 2)));
+        assert_eq!(range.next_back().map(entry_of), None);
+    }
+
+    #[test]
+    fn test_range_borrows_payloads() {
+        let mut tree: AvlTree<isize, Task> = AvlTree::new();
+        for (priority, name) in [(2, "write"), (1, "read"), (3, "send"), (4, "close")] {
+            tree.insert(Node::new(Task(name.to_string()), priority))
+                .unwrap();
+        }
+        let names: Vec<String> = tree
+            .range(2..4)
+            .rev()
+            .map(|entry| entry.index().0.clone())
+            .collect();
+        assert_eq!(names, vec!["send", "write"]);
+    }
+
+    #[test]
+    fn test_range_with_reverse_comparator() {
+        let mut tree = AvlTree::with_comparator(ReverseOrder);
+        for value in 1..=5 {
+            tree.insert(Node::new(0, value)).unwrap();
+        }
+        let values: Vec<isize> = tree
+            .range((Bound::Included(4), Bound::Included(2)))
+            .map(|entry| *entry.value())
+            .collect();
+        assert_eq!(values, vec![4, 3, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn test_range_start_after_end() {
+        let tree = build_free_from_values(vec![1, 2, 3]);
+        tree.range((Bound::Included(3), Bound::Excluded(1))).count();
+    }
+
+    #[test]
+    #[should_panic(expected = "range start and end are equal and excluded")]
+    fn test_range_equal_excluded_bounds() {
+        let tree = build_free_from_values(vec![1, 2, 3]);
+        tree.range((Bound::Excluded(2), Bound::Excluded(2))).count();
+    }
+
+    #[test]
+    fn test_collect_from_iterator() {
+        let tree: IsizeAvlTree = vec![(1, 30), (2, 10), (3, 20), (4, 10)]
+            .into_iter()
+            .collect();
+        assert_eq!(tree.len(), 4);
+        let entries: Vec<(isize, isize)> = tree.iter().map(entry_of).collect();
+        assert_eq!(entries, vec![(2, 10), (4, 10), (3, 20), (1, 30)]);
+        assert!(tree
+            .level_order()
+            .all(|view| view.balance_factor.abs() <= 1));
+    }
+
+    #[test]
+    fn test_collect_with_comparator_type() {
+        let tree: AvlTree<isize, char, ReverseOrder> = "abc".chars().zip(1..).collect();
+        let values: Vec<isize> = tree.iter().map(|entry| *entry.value()).collect();
+        assert_eq!(values, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut tree = build_free_from_values(vec![5, 1]);
+        tree.extend((2..=4).map(|value| (value * 10, value)));
+        let entries: Vec<(isize, isize)> = tree.iter().map(entry_of).collect();
+        assert_eq!(entries, vec![(0, 1), (20, 2), (30, 3), (40, 4), (0, 5)]);
+    }
+
+    #[test]
+    fn test_extend_applies_duplicate_policy() {
+        let mut tree = IsizeAvlTree::new().with_duplicate_policy(DuplicatePolicy::Reject);
+        tree.extend(vec![(1, 10), (2, 20), (3, 10)]);
+        let entries: Vec<(isize, isize)> = tree.iter().map(entry_of).collect();
+        assert_eq!(entries, vec![(1, 10), (2, 20)]);
+    }
+
+    #[test]
+    fn test_into_iter_sorted() {
+        let tree = build_free_from_values(vec![40, 10, 30, 20, 50]);
+        let mut into_iter = tree.into_iter();
+        assert_eq!(into_iter.len(), 5);
+        assert_eq!(into_iter.next(), Some((0, 10)));
+        assert_eq!(into_iter.next_back(), Some((0, 50)));
+        let values: Vec<isize> = into_iter.map(|(_, value)| value).collect();
+        assert_eq!(values, vec![20, 30, 40]);
+    }
+
+    #[test]
+    fn test_into_iter_moves_payloads() {
+        let tree: AvlTree<String, Vec<u8>> = vec![
+            (vec![2], String::from("b")),
+            (vec![1], String::from("a")),
+            (vec![3], String::from("c")),
+        ]
+        .into_iter()
+        .collect();
+        let entries: Vec<(Vec<u8>, String)> = tree.into_iter().collect();
+        assert_eq!(
+            entries,
+            vec![
+                (vec![1], String::from("a")),
+                (vec![2], String::from("b")),
+                (vec![3], String::from("c")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_into_iter_without_clone() {
+        let mut tree: AvlTree<isize, Task> = AvlTree::new();
+        for (priority, name) in [(2, "write"), (1, "read"), (3, "send")] {
+            tree.insert(Node::new(Task(name.to_string()), priority))
+                .unwrap();
+        }
+        let names: Vec<String> = tree.into_iter().map(|(task, _)| task.0).collect();
+        assert_eq!(names, vec!["read", "write", "send"]);
+    }
+
+    #[test]
+    fn test_into_iter_with_outstanding_node_ptr() {
+        let tree = build_free_from_values((1..=10).collect());
+        let node_found = tree.search_tree_by_value(&5).unwrap();
+        let into_iter = tree.into_iter();
+        assert_eq!(into_iter.len(), 9);
+        let values: Vec<isize> = into_iter.map(|(_, value)| value).collect();
+        assert_eq!(values, vec![1, 2, 3, 4, 6, 7, 8, 9, 10]);
+        assert_eq!(node_found.borrow().value, 5);
+        assert!(node_found.borrow().left_child.is_none());
+        assert!(node_found.borrow().detached);
+    }
+
+    #[test]
+    fn test_into_iter_repeats_counted_entries() {
+        let mut tree = IsizeAvlTree::new().with_duplicate_policy(DuplicatePolicy::Count);
+        tree.extend(vec![(0, 2), (0, 1), (0, 2), (0, 2)]);
+        let mut into_iter = tree.into_iter();
+        assert_eq!(into_iter.len(), 4);
+        assert_eq!(into_iter.next_back(), Some((0, 2)));
+        assert_eq!(into_iter.len(), 3);
+        let values: Vec<isize> = into_iter.map(|(_, value)| value).collect();
+        assert_eq!(values, vec![1, 2, 2]);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut tree = build_free_from_values((1..=100).collect());
+        tree.retain(|value, _| value % 3 == 0);
+        assert_eq!(tree.len(), 33);
+        assert_eq!(
+            tree.len(),
+            assert_avl_invariants(tree.root.as_ref().unwrap())
+        );
+        let values: Vec<isize> = tree.iter().map(|entry| *entry.value()).collect();
+        assert_eq!(values, (1..=33).map(|value| value * 3).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_retain_can_modify_kept_indexes() {
+        let mut tree = build_free_from_values(vec![1, 2, 3, 4]);
+        tree.retain(|value, index| {
+            *index = value * 10;
+            value % 2 == 0
+        });
+        let entries: Vec<(isize, isize)> = tree.iter().map(entry_of).collect();
+        assert_eq!(entries, vec![(20, 2), (40, 4)]);
+    }
+
+    #[test]
+    fn test_retain_nothing() {
+        let mut tree = build_free_from_values((1..=20).collect());
+        tree.retain(|_, _| false);
+        assert!(tree.is_empty());
+        assert_eq!(tree.root, None);
+    }
+
+    #[test]
+    fn test_retain_sees_every_duplicate_once() {
+        let mut tree = setup_empty_tree();
+        for index in 0..20 {
+            tree.insert(Node::new(index, index % 2)).unwrap();
+        }
+        let mut seen = Vec::new();
+        tree.retain(|_, index| {
+            seen.push(*index);
+            *index >= 10
+        });
+        seen.sort();
+        assert_eq!(seen, (0..20).collect::<Vec<_>>());
+        let entries: Vec<(isize, isize)> = tree.iter().map(entry_of).collect();
+        assert_eq!(
+            entries,
+            vec![
+                (10, 0),
+                (12, 0),
+                (14, 0),
+                (16, 0),
+                (18, 0),
+                (11, 1),
+                (13, 1),
+                (15, 1),
+                (17, 1),
+                (19, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut tree = build_free_from_values((1..=50).collect());
+        let extracted: Vec<isize> = tree
+            .extract_if(|value, _| value % 5 == 0)
+            .map(|(_, value)| value)
+            .collect();
+        assert_eq!(extracted, vec![5, 10, 15, 20, 25, 30, 35, 40, 45, 50]);
+        assert_eq!(tree.len(), 40);
+        assert_eq!(
+            tree.len(),
+            assert_avl_invariants(tree.root.as_ref().unwrap())
+        );
+        assert!(tree.iter().all(|entry| *entry.value() % 5 != 0));
+    }
+
+    #[test]
+    fn test_extract_if_is_lazy() {
+        let mut tree = build_free_from_values((1..=10).collect());
+        let first_two: Vec<isize> = tree
+            .extract_if(|value, _| value % 2 == 0)
+            .take(2)
+            .map(|(_, value)| value)
+            .collect();
+        assert_eq!(first_two, vec![2, 4]);
+        let values: Vec<isize> = tree.iter().map(|entry| *entry.value()).collect();
+        assert_eq!(values, vec![1, 3, 5, 6, 7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn test_extract_if_returns_owned_payloads() {
+        let mut tree: AvlTree<String, usize> = ["pear", "fig", "apple", "kiwi"]
+            .iter()
+            .map(|name| (name.len(), name.to_string()))
+            .collect();
+        let short: Vec<(usize, String)> = tree.extract_if(|_, len| *len <= 4).collect();
+        assert_eq!(
+            short,
+            vec![
+                (3, String::from("fig")),
+                (4, String::from("kiwi")),
+                (4, String::from("pear"))
+            ]
+        );
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn test_extract_if_without_clone() {
+        let mut tree: AvlTree<isize, Task> = AvlTree::new();
+        for (priority, name) in [(2, "write"), (1, "read"), (3, "send")] {
+            tree.insert(Node::new(Task(name.to_string()), priority))
+                .unwrap();
+        }
+        let names: Vec<String> = tree
+            .extract_if(|priority, _| *priority >= 2)
+            .map(|(task, _)| task.0)
+            .collect();
+        assert_eq!(names, vec!["write", "send"]);
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn test_extract_if_takes_every_counted_entry() {
+        let mut tree = IsizeAvlTree::new().with_duplicate_policy(DuplicatePolicy::Count);
+        tree.extend(vec![(0, 2), (0, 1), (0, 2), (0, 2), (0, 3)]);
+        let mut asked = Vec::new();
+        let extracted: Vec<isize> = tree
+            .extract_if(|value, _| {
+                asked.push(*value);
+                *value == 2
+            })
+            .map(|(_, value)| value)
+            .collect();
+        assert_eq!(extracted, vec![2, 2, 2]);
+        assert_eq!(asked, vec![1, 2, 3]);
+        assert_eq!(tree.len(), 2);
+        assert!(tree.search_tree_by_value(&2).is_none());
+    }
+
+    #[test]
+    fn test_extract_if_leaves_held_node() {
+        let mut tree = build_free_from_values((1..=10).collect());
+        let node_found = tree.search_tree_by_value(&4).unwrap();
+        let extracted: Vec<isize> = tree
+            .extract_if(|value, _| value % 2 == 0)
+            .map(|(_, value)| value)
+            .collect();
+        assert_eq!(extracted, vec![2, 6, 8, 10]);
+        assert_eq!(tree.len(), 6);
+        assert!(Rc::ptr_eq(
+            &tree.search_tree_by_value(&4).unwrap(),
+            &node_found
+        ));
+    }
+
+    #[test]
+    fn test_delete_relinks_successor_node() {
+        let mut tree = build_free_from_values((1..=15).collect());
+        let successor_ptr = tree.search_tree_by_value(&9).unwrap();
+        let root = Rc::clone(tree.root.as_ref().unwrap());
+        assert_eq!(root.borrow().value, 8);
+        tree.delete_node(Rc::clone(&root)).unwrap();
+        assert!(Rc::ptr_eq(tree.root.as_ref().unwrap(), &successor_ptr));
+        assert_eq!(root.borrow().value, 8);
+        assert!(root.borrow().left_child.is_none());
+        assert!(root.borrow().right_child.is_none());
+        assert_eq!(
+            tree.len(),
+            assert_avl_invariants(tree.root.as_ref().unwrap())
+        );
+        assert_eq!(tree.delete_node(root), Err(TreeError::StaleHandle));
+    }
+
+    #[test]
+    fn test_stale_handles_after_their_parent_is_gone() {
+        let mut tree = build_free_from_values((1..=7).collect());
+        let leaf_ptr = tree.search_tree_by_value(&1).unwrap();
+        let parent_ptr = tree.search_tree_by_value(&2).unwrap();
+        tree.delete_node(Rc::clone(&leaf_ptr)).unwrap();
+        tree.delete_node(parent_ptr).unwrap();
+        assert_eq!(
+            tree.delete_node(Rc::clone(&leaf_ptr)),
+            Err(TreeError::StaleHandle)
+        );
+        assert_eq!(tree.left_rotate(leaf_ptr), Err(TreeError::StaleHandle));
+
+        let mut tree = setup_tree1();
+        let root = Rc::clone(tree.root.as_ref().unwrap());
+        tree.delete_node(Rc::clone(&root)).unwrap();
+        assert_eq!(tree.delete_node(root), Err(TreeError::StaleHandle));
+    }
+
+    #[test]
+    fn test_delete_inner_node_keeps_other_handles() {
+        let mut tree = build_free_from_values((1..=31).collect());
+        let node_ptrs: Vec<IsizeNodePtr> = (1..=31)
+            .map(|value| tree.search_tree_by_value(&value).unwrap())
+            .collect();
+        for value in [16, 8, 24, 4, 12, 20, 28] {
+            let node_found = tree.search_tree_by_value(&value).unwrap();
+            tree.delete_node(node_found).unwrap();
+            assert_eq!(
+                tree.len(),
+                assert_avl_invariants(tree.root.as_ref().unwrap())
+            );
+        }
+        for node_ptr in &node_ptrs {
+            let value = node_ptr.borrow().value;
+            let found = tree.search_tree_by_value(&value);
+            match found {
+                Some(found_ptr) => assert!(Rc::ptr_eq(&found_ptr, node_ptr)),
+                None => assert!([16, 8, 24, 4, 12, 20, 28].contains(&value)),
+            }
+        }
+    }
+
+    fn value_of(node_ptr: Option<IsizeNodePtr>) -> Option<isize> {
+        node_ptr.map(|node_ptr| node_ptr.borrow().value)
+    }
+
+    #[test]
+    fn test_predecessor_and_successor() {
+        let tree = build_free_from_values(vec![10, 20, 30, 40]);
+        assert_eq!(value_of(tree.predecessor(&30)), Some(20));
+        assert_eq!(value_of(tree.predecessor(&25)), Some(20));
+        assert_eq!(value_of(tree.predecessor(&10)), None);
+        assert_eq!(value_of(tree.successor(&30)), Some(40));
+        assert_eq!(value_of(tree.successor(&35)), Some(40));
+        assert_eq!(value_of(tree.successor(&40)), None);
+        assert_eq!(value_of(tree.successor(&0)), Some(10));
+    }
+
+    #[test]
+    fn test_floor_and_ceiling() {
+        let tree = build_free_from_values(vec![10, 20, 30, 40]);
+        assert_eq!(value_of(tree.floor(&30)), Some(30));
+        assert_eq!(value_of(tree.floor(&35)), Some(30));
+        assert_eq!(value_of(tree.floor(&5)), None);
+        assert_eq!(value_of(tree.ceiling(&30)), Some(30));
+        assert_eq!(value_of(tree.ceiling(&25)), Some(30));
+        assert_eq!(value_of(tree.ceiling(&45)), None);
+        assert_eq!(value_of(setup_empty_tree().floor(&1)), None);
+    }
+
+    #[test]
+    fn test_bounds_among_duplicates() {
+        let mut tree = setup_empty_tree();
+        for (index, value) in [(1, 10), (2, 20), (3, 20), (4, 30), (5, 20)] {
+            tree.insert(Node::new(index, value)).unwrap();
+        }
+        let index_of = |node_ptr: Option<IsizeNodePtr>| node_ptr.map(|node| node.borrow().index);
+        assert_eq!(index_of(tree.ceiling(&20)), Some(2));
+        assert_eq!(index_of(tree.floor(&20)), Some(5));
+        assert_eq!(index_of(tree.predecessor(&20)), Some(1));
+        assert_eq!(index_of(tree.successor(&20)), Some(4));
+    }
+
+    #[test]
+    fn test_bounds_match_linear_scan() {
+        let values: Vec<isize> = (0..100).map(|value| (value * 37) % 211).collect();
+        let tree = build_free_from_values(values.clone());
+        for key in -1..212 {
+            let below = values.iter().filter(|value| **value < key).max().copied();
+            let above = values.iter().filter(|value| **value > key).min().copied();
+            let at_most = values.iter().filter(|value| **value <= key).max().copied();
+            let at_least = values.iter().filter(|value| **value >= key).min().copied();
+            assert_eq!(value_of(tree.predecessor(&key)), below);
+            assert_eq!(value_of(tree.successor(&key)), above);
+            assert_eq!(value_of(tree.floor(&key)), at_most);
+            assert_eq!(value_of(tree.ceiling(&key)), at_least);
+        }
+    }
+
+    #[test]
+    fn test_first_and_last() {
+        let tree = build_free_from_values(vec![30, 10, 20]);
+        assert_eq!(tree.first().map(entry_of), Some((0, 10)));
+        assert_eq!(tree.last().map(entry_of), Some((0, 30)));
+        assert_eq!(tree.len(), 3);
+        assert!(setup_empty_tree().first().is_none());
+        assert!(setup_empty_tree().last().is_none());
+    }
+
+    #[test]
+    fn test_first_and_last_among_duplicates() {
+        let mut tree = setup_empty_tree();
+        for (index, value) in [(1, 5), (2, 5), (3, 5)] {
+            tree.insert(Node::new(index, value)).unwrap();
+        }
+        assert_eq!(tree.first().map(entry_of), Some((1, 5)));
+        assert_eq!(tree.last().map(entry_of), Some((3, 5)));
+        assert_eq!(tree.pop_first(), Some((1, 5)));
+        assert_eq!(tree.pop_last(), Some((3, 5)));
+        assert_eq!(tree.pop_last(), Some((2, 5)));
+        assert_eq!(tree.pop_first(), None);
+    }
+
+    #[test]
+    fn test_pop_takes_one_counted_entry() {
+        let mut tree = IsizeAvlTree::new().with_duplicate_policy(DuplicatePolicy::Count);
+        for value in [5, 7, 5, 5] {
+            tree.insert(Node::new(0, value)).unwrap();
+        }
+        assert_eq!(tree.len(), 4);
+        let mut popped = Vec::new();
+        while let Some(entry) = tree.pop_first() {
+            popped.push(entry);
+            assert_eq!(tree.len(), 4 - popped.len());
+        }
+        assert_eq!(popped, vec![(0, 5), (0, 5), (0, 5), (0, 7)]);
+        for value in [5, 7, 7] {
+            tree.insert(Node::new(0, value)).unwrap();
+        }
+        assert_eq!(tree.pop_last(), Some((0, 7)));
+        assert_eq!(tree.last().map(entry_of), Some((0, 7)));
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    fn test_drain_large_tree_ascending() {
+        let size = 2000;
+        let mut tree =
+            build_free_from_values((0..size).map(|value| (value * 7919) % size).collect());
+        for expected in 0..size {
+            assert_eq!(tree.pop_first(), Some((0, expected)));
+            if expected % 97 == 0 && !tree.is_empty() {
+                assert_eq!(
+                    tree.len(),
+                    assert_avl_invariants(tree.root.as_ref().unwrap())
+                );
+            }
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.pop_first(), None);
+    }
+
+    #[test]
+    fn test_drain_large_tree_descending() {
+        let size = 2000;
+        let mut tree =
+            build_free_from_values((0..size).map(|value| (value * 7919) % size).collect());
+        for expected in (0..size).rev() {
+            assert_eq!(tree.pop_last(), Some((0, expected)));
+            if expected % 97 == 0 && !tree.is_empty() {
+                assert_eq!(
+                    tree.len(),
+                    assert_avl_invariants(tree.root.as_ref().unwrap())
+                );
+            }
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.pop_last(), None);
+    }
+
+    #[test]
+    fn test_pop_from_both_ends() {
+        let mut tree = build_free_from_values((1..=100).collect());
+        for step in 0..50 {
+            assert_eq!(tree.pop_first(), Some((0, step + 1)));
+            assert_eq!(tree.pop_last(), Some((0, 100 - step)));
+        }
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn test_select() {
+        let tree = build_free_from_values((0..100).map(|value| value * 2).rev().collect());
+        for k in 0..100 {
+            let node_ptr = tree.select(k).unwrap();
+            assert_eq!(node_ptr.borrow().value, k as isize * 2);
+        }
+        assert!(tree.select(100).is_none());
+        assert!(setup_empty_tree().select(0).is_none());
+    }
+
+    #[test]
+    fn test_rank() {
+        let tree = build_free_from_values((0..100).map(|value| value * 2).collect());
+        assert_eq!(tree.rank(&-5), 0);
+        assert_eq!(tree.rank(&0), 0);
+        assert_eq!(tree.rank(&1), 1);
+        assert_eq!(tree.rank(&50), 25);
+        assert_eq!(tree.rank(&51), 26);
+        assert_eq!(tree.rank(&1000), 100);
+        assert_eq!(setup_empty_tree().rank(&3), 0);
+    }
+
+    #[test]
+    fn test_rank_and_select_agree() {
+        let tree = build_free_from_values((0..300).map(|value| (value * 7) % 300).collect());
+        for k in 0..300 {
+            let value = tree.select(k).unwrap().borrow().value;
+            assert_eq!(tree.rank(&value), k);
+        }
+    }
+
+    #[test]
+    fn test_sizes_kept_through_deletions() {
+        let mut tree = build_free_from_values((0..200).collect());
+        for value in (0..200).filter(|value| value % 4 != 1) {
+            tree.remove(&value).unwrap();
+            assert_eq!(
+                tree.root.as_ref().unwrap().borrow().size,
+                assert_avl_invariants(tree.root.as_ref().unwrap())
+            );
+        }
+        for k in 0..50 {
+            assert_eq!(tree.select(k).unwrap().borrow().value, k as isize * 4 + 1);
+        }
+    }
+
+    #[test]
+    fn test_rank_and_select_with_duplicates() {
+        let mut tree = setup_empty_tree();
+        for (index, value) in [(1, 10), (2, 20), (3, 20), (4, 30)] {
+            tree.insert(Node::new(index, value)).unwrap();
+        }
+        assert_eq!(tree.rank(&20), 1);
+        assert_eq!(tree.rank(&30), 3);
+        assert_eq!(tree.select(1).unwrap().borrow().index, 2);
+        assert_eq!(tree.select(2).unwrap().borrow().index, 3);
+    }
+
+    #[test]
+    fn test_rank_and_select_count_multiplicity() {
+        let mut tree = IsizeAvlTree::new().with_duplicate_policy(DuplicatePolicy::Count);
+        for value in [10, 20, 20, 20, 30] {
+            tree.insert(Node::new(0, value)).unwrap();
+        }
+        assert_eq!(tree.root.as_ref().unwrap().borrow().size, 5);
+        assert_eq!(tree.rank(&30), 4);
+        let selected: Vec<isize> = (0..5)
+            .map(|k| tree.select(k).unwrap().borrow().value)
+            .collect();
+        assert_eq!(selected, vec![10, 20, 20, 20, 30]);
+        tree.remove(&20);
+        assert_eq!(tree.rank(&30), 3);
+        assert_eq!(tree.root.as_ref().unwrap().borrow().size, 4);
+        assert_eq!(tree.select(3).unwrap().borrow().value, 30);
+    }
 }
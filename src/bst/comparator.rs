@@ -0,0 +1,36 @@
+use std::cmp::Ordering;
+
+// Decides the order of keys inside an AvlTree; any Fn(&K, &K) -> Ordering closure is a comparator
+pub trait Comparator<K> {
+    fn compare(&self, left: &K, right: &K) -> Ordering;
+}
+
+// Ascending order given by the key's own Ord implementation
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NaturalOrder;
+
+impl<K: Ord> Comparator<K> for NaturalOrder {
+    fn compare(&self, left: &K, right: &K) -> Ordering {
+        left.cmp(right)
+    }
+}
+
+// Descending order given by the key's own Ord implementation
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(dead_code)]
+pub struct ReverseOrder;
+
+impl<K: Ord> Comparator<K> for ReverseOrder {
+    fn compare(&self, left: &K, right: &K) -> Ordering {
+        right.cmp(left)
+    }
+}
+
+impl<K, F> Comparator<K> for F
+where
+    F: Fn(&K, &K) -> Ordering,
+{
+    fn compare(&self, left: &K, right: &K) -> Ordering {
+        self(left, right)
+    }
+}
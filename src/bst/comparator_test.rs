@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use super::super::avl_node::*;
+    use super::super::avl_tree::*;
+    use super::super::comparator::*;
+    use std::cmp::Ordering;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    fn setup_reverse_tree() -> AvlTree<isize, isize, ReverseOrder> {
+        let mut tree = AvlTree::with_comparator(ReverseOrder);
+        for value in [30, 50, 70, 65, 68] {
+            tree.insert(Node::new(0, value)).unwrap();
+        }
+        tree
+    }
+
+    #[test]
+    fn test_natural_order_compare() {
+        assert_eq!(NaturalOrder.compare(&1, &2), Ordering::Less);
+        assert_eq!(NaturalOrder.compare(&2, &2), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_reverse_order_compare() {
+        assert_eq!(ReverseOrder.compare(&1, &2), Ordering::Greater);
+        assert_eq!(ReverseOrder.compare(&2, &2), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_reverse_tree_insertion() {
+        let tree = setup_reverse_tree();
+        assert_eq!(
+            convert_node_to_vec(tree.root.unwrap()),
+            VecDeque::from(vec![50, 68, 30, 70, 65])
+        );
+    }
+
+    #[test]
+    fn test_reverse_tree_min_max() {
+        let tree = setup_reverse_tree();
+        let root = Rc::clone(tree.root.as_ref().unwrap());
+        let min_child = AvlTree::<isize, isize, ReverseOrder>::get_minimum_child(Rc::clone(&root));
+        let max_child = AvlTree::<isize, isize, ReverseOrder>::get_maximum_child(root);
+        assert_eq!(min_child.borrow().value, 70);
+        assert_eq!(max_child.borrow().value, 30);
+    }
+
+    #[test]
+    fn test_reverse_tree_search_and_delete() {
+        let mut tree = setup_reverse_tree();
+        let node_found = tree.search_tree_by_value(&65);
+        assert_eq!(node_found.as_ref().unwrap().borrow().value, 65);
+        tree.delete_node(node_found.unwrap()).unwrap();
+        assert_eq!(tree.search_tree_by_value(&65), None);
+        assert_eq!(
+            convert_node_to_vec(tree.root.unwrap()),
+            VecDeque::from(vec![50, 68, 30, 70])
+        );
+    }
+
+    #[test]
+    fn test_case_insensitive_closure() {
+        let case_insensitive =
+            |left: &String, right: &String| left.to_lowercase().cmp(&right.to_lowercase());
+        let mut tree = AvlTree::with_comparator(case_insensitive);
+        for (index, word) in ["banana", "Apple", "cherry"].iter().enumerate() {
+            tree.insert(Node::new(index, word.to_string())).unwrap();
+        }
+        let node_found = tree.search_tree_by_value(&"APPLE".to_string());
+        assert_eq!(node_found.unwrap().borrow().index, 1);
+        let min_child = AvlTree::<String, usize>::get_minimum_child(tree.root.unwrap());
+        assert_eq!(min_child.borrow().value, "Apple");
+    }
+
+    #[test]
+    fn test_composite_key_closure() {
+        // Order by department ascending, then by salary descending
+        let by_department_then_salary =
+            |left: &(u32, u32), right: &(u32, u32)| left.0.cmp(&right.0).then(right.1.cmp(&left.1));
+        let mut tree = AvlTree::with_comparator(by_department_then_salary);
+        for key in [(2, 100), (1, 50), (1, 80), (2, 300)] {
+            tree.insert(Node::new(0, key)).unwrap();
+        }
+        let root = Rc::clone(tree.root.as_ref().unwrap());
+        let min_child = AvlTree::<(u32, u32), i32>::get_minimum_child(Rc::clone(&root));
+        let max_child = AvlTree::<(u32, u32), i32>::get_maximum_child(root);
+        assert_eq!(min_child.borrow().value, (1, 80));
+        assert_eq!(max_child.borrow().value, (2, 100));
+        assert!(tree.search_tree_by_value(&(2, 300)).is_some());
+        assert!(tree.search_tree_by_value(&(2, 200)).is_none());
+    }
+}
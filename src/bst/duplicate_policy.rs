@@ -0,0 +1,13 @@
+// What AvlTree::insert does with a key that is already in the tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    // Keep every duplicate as its own node, in insertion order
+    #[default]
+    KeepAll,
+    // Refuse the new node with TreeError::DuplicateKey
+    Reject,
+    // Overwrite the index of the node already holding the key
+    Replace,
+    // Keep the first node and bump its count
+    Count,
+}
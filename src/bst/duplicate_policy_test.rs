@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use super::super::avl_node::*;
+    use super::super::avl_tree::*;
+    use super::super::duplicate_policy::DuplicatePolicy;
+    use super::super::tree_error::TreeError;
+
+    fn setup_tree(duplicate_policy: DuplicatePolicy) -> IsizeAvlTree {
+        let mut tree = IsizeAvlTree::new().with_duplicate_policy(duplicate_policy);
+        for (index, value) in [
+            (0, 20),
+            (1, 10),
+            (2, 20),
+            (3, 30),
+            (4, 20),
+            (5, 10),
+            (6, 20),
+        ] {
+            let _ = tree.insert(Node::new(index, value));
+        }
+        tree
+    }
+
+    fn equal_indexes(tree: &IsizeAvlTree, key: isize) -> Vec<isize> {
+        tree.equal_range(&key)
+            .map(|node_ptr| node_ptr.borrow().index)
+            .collect()
+    }
+
+    #[test]
+    fn test_default_policy_is_keep_all() {
+        assert_eq!(
+            IsizeAvlTree::new().duplicate_policy(),
+            DuplicatePolicy::KeepAll
+        );
+    }
+
+    #[test]
+    fn test_keep_all_in_insertion_order() {
+        let tree = setup_tree(DuplicatePolicy::KeepAll);
+        assert_eq!(equal_indexes(&tree, 20), vec![0, 2, 4, 6]);
+        assert_eq!(equal_indexes(&tree, 10), vec![1, 5]);
+        assert_eq!(equal_indexes(&tree, 30), vec![3]);
+    }
+
+    #[test]
+    fn test_keep_all_search_finds_earliest() {
+        let tree = setup_tree(DuplicatePolicy::KeepAll);
+        assert_eq!(tree.search_tree_by_value(&20).unwrap().borrow().index, 0);
+        assert_eq!(tree.search_tree_by_value(&10).unwrap().borrow().index, 1);
+    }
+
+    #[test]
+    fn test_keep_all_remove_in_insertion_order() {
+        let mut tree = setup_tree(DuplicatePolicy::KeepAll);
+        assert_eq!(tree.remove(&20), Some((0, 20)));
+        assert_eq!(tree.remove(&20), Some((2, 20)));
+        assert_eq!(equal_indexes(&tree, 20), vec![4, 6]);
+    }
+
+    #[test]
+    fn test_equal_range_of_missing_key() {
+        let tree = setup_tree(DuplicatePolicy::KeepAll);
+        assert!(tree.equal_range(&15).next().is_none());
+        assert!(IsizeAvlTree::new().equal_range(&15).next().is_none());
+    }
+
+    #[test]
+    fn test_reject() {
+        let mut tree = IsizeAvlTree::new().with_duplicate_policy(DuplicatePolicy::Reject);
+        assert_eq!(tree.insert(Node::new(0, 20)), Ok(()));
+        assert_eq!(tree.insert(Node::new(1, 20)), Err(TreeError::DuplicateKey));
+        assert_eq!(equal_indexes(&tree, 20), vec![0]);
+    }
+
+    #[test]
+    fn test_replace() {
+        let tree = setup_tree(DuplicatePolicy::Replace);
+        assert_eq!(equal_indexes(&tree, 20), vec![6]);
+        assert_eq!(equal_indexes(&tree, 10), vec![5]);
+    }
+
+    #[test]
+    fn test_count() {
+        let tree = setup_tree(DuplicatePolicy::Count);
+        let node_ptr = tree.search_tree_by_value(&20).unwrap();
+        assert_eq!(node_ptr.borrow().count, 4);
+        assert_eq!(node_ptr.borrow().index, 0);
+        assert_eq!(equal_indexes(&tree, 20), vec![0]);
+        assert_eq!(tree.search_tree_by_value(&30).unwrap().borrow().count, 1);
+    }
+
+    #[test]
+    fn test_count_remove_takes_one_entry() {
+        let mut tree = setup_tree(DuplicatePolicy::Count);
+        let len = tree.len();
+        assert_eq!(tree.remove(&10), Some((1, 10)));
+        assert_eq!(tree.search_tree_by_value(&10).unwrap().borrow().count, 1);
+        assert_eq!(tree.remove(&10), Some((1, 10)));
+        assert!(tree.search_tree_by_value(&10).is_none());
+        assert_eq!(tree.remove(&20), Some((0, 20)));
+        assert_eq!(tree.search_tree_by_value(&20).unwrap().borrow().count, 3);
+        assert_eq!(tree.len(), len - 3);
+    }
+}
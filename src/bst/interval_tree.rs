@@ -0,0 +1,222 @@
+use crate::bst::avl_node::{Node, NodePtr};
+use crate::bst::avl_tree::{self, AvlTree};
+use crate::bst::comparator::NaturalOrder;
+use crate::bst::summary::IndexMax;
+use crate::bst::tree_error::TreeError;
+use std::marker::PhantomData;
+use std::ops::{Bound, Range};
+use std::rc::Rc;
+
+// Interval facade over AvlTree: an interval [start, end) is stored with its start as the node
+// value and its end as the node index, and every subtree keeps the largest end found in it
+// through rotations, so whole subtrees ending before a query can be skipped. Inserts and
+// removals stay O(log n) with one end per node; a query reaching k matches costs
+// O((k + 1) log n). Equal intervals may be stored together
+#[derive(Debug)]
+pub struct IntervalTree<T> {
+    tree: AvlTree<T, T, NaturalOrder, IndexMax<T>>,
+}
+
+// Walks the intervals of an IntervalTree in order of their start
+pub struct Iter<'a, T> {
+    inner: avl_tree::Iter<'a, T, T, IndexMax<T>>,
+}
+
+// Intervals ending after start and beginning before end, in order of their start. Subtrees
+// whose largest end is not after start are never entered and the walk stops at the first
+// interval beginning past end, so every match costs at most one O(log n) descent
+pub struct Overlapping<'a, T> {
+    stack: Vec<NodePtr<T, T, IndexMax<T>>>,
+    start: T,
+    end: Bound<T>,
+    repeated: Option<(Range<T>, usize)>,
+    marker: PhantomData<&'a ()>,
+}
+
+impl<T: Ord + Clone> Default for IntervalTree<T> {
+    fn default() -> Self {
+        IntervalTree::new()
+    }
+}
+
+impl<T: Ord + Clone> IntervalTree<T> {
+    pub fn new() -> IntervalTree<T> {
+        IntervalTree {
+            tree: AvlTree::summarized(NaturalOrder),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.tree.iter(),
+        }
+    }
+
+    // Largest end among all intervals
+    pub fn max_end(&self) -> Option<T> {
+        self.tree.root.as_ref()?.borrow().summary.0.clone()
+    }
+
+    pub fn insert(&mut self, interval: Range<T>) -> Result<(), TreeError> {
+        if interval.start >= interval.end {
+            return Err(TreeError::EmptyInterval);
+        }
+        self.tree.insert(Node::new(interval.end, interval.start))
+    }
+
+    // Removes one interval equal to the given one, returning false when there is none
+    pub fn remove(&mut self, interval: &Range<T>) -> bool {
+        let mut current_node_ptr_option = self.tree.ceiling(&interval.start);
+        while let Some(current_node_ptr) = current_node_ptr_option {
+            let (is_same_start, is_same_end) = {
+                let node = current_node_ptr.borrow();
+                (node.value == interval.start, node.index == interval.end)
+            };
+            if !is_same_start {
+                break;
+            }
+            if is_same_end {
+                self.tree.unlink_node(current_node_ptr);
+                return true;
+            }
+            current_node_ptr_option =
+                AvlTree::<T, T, NaturalOrder, IndexMax<T>>::get_next_node(&current_node_ptr);
+        }
+        false
+    }
+
+    // Intervals sharing at least one point with query; an empty query overlaps nothing
+    pub fn overlapping(&self, query: Range<T>) -> Overlapping<'_, T> {
+        if query.start >= query.end {
+            return Overlapping::from_root(None, query.start, Bound::Unbounded);
+        }
+        Overlapping::from_root(
+            self.tree.root.as_ref(),
+            query.start,
+            Bound::Excluded(query.end),
+        )
+    }
+
+    // Intervals with start <= point < end
+    pub fn containing(&self, point: T) -> Overlapping<'_, T> {
+        Overlapping::from_root(
+            self.tree.root.as_ref(),
+            point.clone(),
+            Bound::Included(point),
+        )
+    }
+
+    pub fn any_overlap(&self, query: Range<T>) -> bool {
+        self.overlapping(query).next().is_some()
+    }
+}
+
+impl<'a, T: Ord + Clone> Overlapping<'a, T> {
+    fn from_root(
+        root: Option<&'a NodePtr<T, T, IndexMax<T>>>,
+        start: T,
+        end: Bound<T>,
+    ) -> Overlapping<'a, T> {
+        let mut overlapping = Overlapping {
+            stack: Vec::new(),
+            start,
+            end,
+            repeated: None,
+            marker: PhantomData,
+        };
+        overlapping.push_left_spine(root.map(Rc::clone));
+        overlapping
+    }
+
+    // Stacks the way down to the leftmost node, stopping at the first subtree that ends too
+    // early to hold a match
+    fn push_left_spine(&mut self, mut node_ptr_option: Option<NodePtr<T, T, IndexMax<T>>>) {
+        while let Some(node_ptr) = node_ptr_option {
+            let ends_after_start = match &node_ptr.borrow().summary.0 {
+                Some(max_end) => *max_end > self.start,
+                None => false,
+            };
+            if !ends_after_start {
+                break;
+            }
+            node_ptr_option = node_ptr.borrow().get_left_child();
+            self.stack.push(node_ptr);
+        }
+    }
+
+    fn starts_before_end(&self, start: &T) -> bool {
+        match &self.end {
+            Bound::Included(end) => start <= end,
+            Bound::Excluded(end) => start < end,
+            Bound::Unbounded => true,
+        }
+    }
+}
+
+impl<'a, T: Clone> Iterator for Iter<'a, T> {
+    type Item = Range<T>;
+
+    fn next(&mut self) -> Option<Range<T>> {
+        self.inner
+            .next()
+            .map(|entry| entry.value().clone()..entry.index().clone())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T: Clone> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Range<T>> {
+        self.inner
+            .next_back()
+            .map(|entry| entry.value().clone()..entry.index().clone())
+    }
+}
+
+impl<'a, T: Clone> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T: Ord + Clone> Iterator for Overlapping<'a, T> {
+    type Item = Range<T>;
+
+    fn next(&mut self) -> Option<Range<T>> {
+        if let Some((interval, remaining)) = &mut self.repeated {
+            *remaining -= 1;
+            let interval = interval.clone();
+            if *remaining == 0 {
+                self.repeated = None;
+            }
+            return Some(interval);
+        }
+        while let Some(node_ptr) = self.stack.pop() {
+            let (start, end, count) = {
+                let node = node_ptr.borrow();
+                (node.value.clone(), node.index.clone(), node.count)
+            };
+            // Everything left on the stack or to the right starts even later
+            if !self.starts_before_end(&start) {
+                self.stack.clear();
+                return None;
+            }
+            self.push_left_spine(node_ptr.borrow().get_right_child());
+            if end > self.start {
+                let interval = start..end;
+                // An entry counted several times is handed out that many times
+                if count > 1 {
+                    self.repeated = Some((interval.clone(), count - 1));
+                }
+                return Some(interval);
+            }
+        }
+        None
+    }
+}
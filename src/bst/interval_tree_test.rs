@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests {
+    use super::super::interval_tree::*;
+    use super::super::tree_error::TreeError;
+    use std::ops::Range;
+
+    // Deterministic spread of intervals with many shared starts and nested ranges
+    fn sample_intervals() -> Vec<Range<isize>> {
+        (0..120)
+            .map(|step| {
+                let start = (step * 37) % 101;
+                start..start + 1 + (step * 13) % 17
+            })
+            .collect()
+    }
+
+    fn setup_interval_tree(intervals: &[Range<isize>]) -> IntervalTree<isize> {
+        let mut tree = IntervalTree::new();
+        for interval in intervals {
+            tree.insert(interval.clone()).unwrap();
+        }
+        tree
+    }
+
+    fn sorted(intervals: Vec<Range<isize>>) -> Vec<(isize, isize)> {
+        let mut pairs: Vec<_> = intervals
+            .into_iter()
+            .map(|interval| (interval.start, interval.end))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn test_overlapping_matches_a_linear_scan() {
+        let intervals = sample_intervals();
+        let tree = setup_interval_tree(&intervals);
+        for start in -2..110 {
+            for length in 1..6 {
+                let query = start..start + length;
+                let expected: Vec<_> = intervals
+                    .iter()
+                    .filter(|interval| interval.start < query.end && query.start < interval.end)
+                    .cloned()
+                    .collect();
+                let found: Vec<_> = tree.overlapping(query.clone()).collect();
+                assert_eq!(sorted(found), sorted(expected.clone()));
+                assert_eq!(tree.any_overlap(query), !expected.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_containing_matches_a_linear_scan() {
+        let intervals = sample_intervals();
+        let tree = setup_interval_tree(&intervals);
+        for point in -2..120 {
+            let expected: Vec<_> = intervals
+                .iter()
+                .filter(|interval| interval.contains(&point))
+                .cloned()
+                .collect();
+            assert_eq!(sorted(tree.containing(point).collect()), sorted(expected));
+        }
+    }
+
+    #[test]
+    fn test_half_open_ends_do_not_overlap() {
+        let tree = setup_interval_tree(&[0..5, 5..10]);
+        assert_eq!(tree.overlapping(5..7).collect::<Vec<_>>(), vec![5..10]);
+        assert_eq!(tree.containing(5).collect::<Vec<_>>(), vec![5..10]);
+        assert!(!tree.any_overlap(10..12));
+        assert!(!tree.any_overlap(3..3));
+    }
+
+    #[test]
+    fn test_insert_rejects_empty_intervals() {
+        let mut tree = IntervalTree::new();
+        assert_eq!(tree.insert(4..4), Err(TreeError::EmptyInterval));
+        assert_eq!(
+            tree.insert(Range { start: 5, end: 2 }),
+            Err(TreeError::EmptyInterval)
+        );
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn test_remove_keeps_max_end_up_to_date() {
+        let mut tree = setup_interval_tree(&[1..3, 2..50, 2..4, 10..12, 2..50]);
+        assert_eq!(tree.max_end(), Some(50));
+        assert!(tree.remove(&(2..50)));
+        assert_eq!(tree.max_end(), Some(50));
+        assert!(tree.remove(&(2..50)));
+        assert!(!tree.remove(&(2..50)));
+        assert_eq!(tree.max_end(), Some(12));
+        assert_eq!(tree.len(), 3);
+        assert!(!tree.any_overlap(20..30));
+        assert_eq!(tree.iter().collect::<Vec<_>>(), vec![1..3, 2..4, 10..12]);
+    }
+
+    #[test]
+    fn test_queries_after_many_removals() {
+        let intervals = sample_intervals();
+        let mut tree = setup_interval_tree(&intervals);
+        let (removed, kept): (Vec<_>, Vec<_>) = intervals
+            .iter()
+            .cloned()
+            .enumerate()
+            .partition(|(position, _)| position % 3 == 0);
+        for (_, interval) in &removed {
+            assert!(tree.remove(interval));
+        }
+        let kept: Vec<_> = kept.into_iter().map(|(_, interval)| interval).collect();
+        assert_eq!(tree.len(), kept.len());
+        assert_eq!(
+            tree.max_end(),
+            kept.iter().map(|interval| interval.end).max()
+        );
+        for point in 0..120 {
+            let expected: Vec<_> = kept
+                .iter()
+                .filter(|interval| interval.contains(&point))
+                .cloned()
+                .collect();
+            assert_eq!(sorted(tree.containing(point).collect()), sorted(expected));
+        }
+    }
+
+    #[test]
+    fn test_scattered_long_intervals_among_short_ones() {
+        let mut tree = IntervalTree::new();
+        let mut long_intervals = Vec::new();
+        for start in (0..10_000).rev() {
+            let interval = if start % 997 == 0 {
+                start..start + 1_000_000
+            } else {
+                start..start + 1
+            };
+            if interval.end > 20_000 {
+                long_intervals.push(interval.clone());
+            }
+            tree.insert(interval).unwrap();
+        }
+        assert_eq!(tree.max_end(), Some(9_970 + 1_000_000));
+        let found: Vec<_> = tree.containing(20_000).collect();
+        assert_eq!(sorted(found), sorted(long_intervals));
+        assert_eq!(
+            sorted(tree.overlapping(5_000..5_002).collect()),
+            sorted(
+                (0..10_000)
+                    .filter(|start| start % 997 == 0 && *start < 5_002)
+                    .map(|start| start..start + 1_000_000)
+                    .chain([5_000..5_001, 5_001..5_002])
+                    .collect()
+            )
+        );
+    }
+}
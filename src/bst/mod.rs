@@ -1,6 +1,37 @@
+pub mod avl_cursor;
+pub mod avl_entry;
+// The facades are only exercised by the tests, main doesn't reach them
+#[allow(dead_code)]
+pub mod avl_map;
 pub mod avl_node;
+#[allow(dead_code)]
+pub mod avl_sequence;
+#[allow(dead_code)]
+pub mod avl_set;
+pub mod avl_traversal;
 pub mod avl_tree;
+pub mod comparator;
+pub mod duplicate_policy;
+#[allow(dead_code)]
+pub mod interval_tree;
+pub mod node_handle;
+#[allow(dead_code)]
+pub mod rope;
+pub mod summary;
+pub mod tree_error;
 
+mod avl_cursor_test;
+mod avl_entry_test;
+mod avl_map_test;
 #[cfg(test)]
 mod avl_node_tests;
+mod avl_sequence_test;
+mod avl_set_test;
+mod avl_traversal_test;
 mod avl_tree_test;
+mod comparator_test;
+mod duplicate_policy_test;
+mod interval_tree_test;
+mod node_handle_test;
+mod rope_test;
+mod summary_test;
@@ -0,0 +1,75 @@
+use crate::bst::avl_node::{NodePtr, WeakNodePtr};
+use crate::bst::avl_tree::AvlTree;
+use crate::bst::comparator::Comparator;
+use crate::bst::summary::Summary;
+use crate::bst::tree_error::TreeError;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_TREE_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) fn next_tree_id() -> usize {
+    NEXT_TREE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+// Refers to one node of one AvlTree without keeping it alive. Deleting other entries never
+// moves a payload between nodes, so the handle keeps pointing at the same entry until that
+// entry itself is removed
+pub struct NodeHandle<K, V, S = ()> {
+    node: WeakNodePtr<K, V, S>,
+    tree_id: usize,
+}
+
+impl<K, V, S> Clone for NodeHandle<K, V, S> {
+    fn clone(&self) -> Self {
+        NodeHandle {
+            node: self.node.clone(),
+            tree_id: self.tree_id,
+        }
+    }
+}
+
+impl<K, V, S> fmt::Debug for NodeHandle<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeHandle")
+            .field("tree_id", &self.tree_id)
+            .field("is_alive", &(self.node.strong_count() > 0))
+            .finish()
+    }
+}
+
+impl<K, V, C: Comparator<K>, S: Summary<K, V>> AvlTree<K, V, C, S> {
+    pub fn handle(&self, node_ptr: &NodePtr<K, V, S>) -> Result<NodeHandle<K, V, S>, TreeError> {
+        self.check_membership(node_ptr)?;
+        Ok(NodeHandle {
+            node: Rc::downgrade(node_ptr),
+            tree_id: self.id(),
+        })
+    }
+
+    // Handle to the earliest inserted node holding key
+    pub fn find_handle(&self, key: &K) -> Option<NodeHandle<K, V, S>> {
+        let node_ptr = self.search_tree_by_value(key)?;
+        Some(NodeHandle {
+            node: Rc::downgrade(&node_ptr),
+            tree_id: self.id(),
+        })
+    }
+
+    pub fn resolve(&self, handle: &NodeHandle<K, V, S>) -> Result<NodePtr<K, V, S>, TreeError> {
+        if handle.tree_id != self.id() {
+            return Err(TreeError::ForeignNode);
+        }
+        let node_ptr = handle.node.upgrade().ok_or(TreeError::StaleHandle)?;
+        self.check_membership(&node_ptr)
+            .map_err(|_| TreeError::StaleHandle)?;
+        Ok(node_ptr)
+    }
+
+    // Removes one entry of the node, like remove does for a key
+    pub fn remove_by_handle(&mut self, handle: &NodeHandle<K, V, S>) -> Result<(V, K), TreeError> {
+        let node_ptr = self.resolve(handle)?;
+        self.take_entry(node_ptr)
+    }
+}
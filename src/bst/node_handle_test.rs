@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use super::super::avl_node::*;
+    use super::super::avl_tree::*;
+    use super::super::duplicate_policy::DuplicatePolicy;
+    use super::super::tree_error::TreeError;
+
+    #[test]
+    fn test_handle_survives_deletion_of_predecessor() {
+        // 4 is the root with two children and 5 is its in-order successor
+        let mut tree = build_free_from_values((1..=7).collect());
+        let handle = tree.find_handle(&5).unwrap();
+        tree.remove(&4).unwrap();
+        let node_ptr = tree.resolve(&handle).unwrap();
+        assert_eq!(node_ptr.borrow().value, 5);
+        assert!(tree
+            .level_order()
+            .all(|view| view.balance_factor.abs() <= 1));
+    }
+
+    #[test]
+    fn test_handles_survive_many_deletions() {
+        let mut tree = build_free_from_values((0..200).collect());
+        let handles: Vec<_> = (0..200)
+            .map(|value| tree.find_handle(&value).unwrap())
+            .collect();
+        for value in (0..200).filter(|value| value % 3 != 0) {
+            tree.remove(&value).unwrap();
+        }
+        for (value, handle) in (0..200).zip(&handles) {
+            match tree.resolve(handle) {
+                Ok(node_ptr) => {
+                    assert_eq!(value % 3, 0);
+                    assert_eq!(node_ptr.borrow().value, value);
+                }
+                Err(error) => {
+                    assert_ne!(value % 3, 0);
+                    assert_eq!(error, TreeError::StaleHandle);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_stale_handle_is_rejected() {
+        let mut tree = build_free_from_values(vec![1, 2, 3]);
+        let handle = tree.find_handle(&2).unwrap();
+        assert_eq!(tree.remove_by_handle(&handle), Ok((0, 2)));
+        assert_eq!(tree.resolve(&handle).err(), Some(TreeError::StaleHandle));
+        assert_eq!(tree.remove_by_handle(&handle), Err(TreeError::StaleHandle));
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    fn test_stale_handle_with_node_kept_alive() {
+        let mut tree = build_free_from_values(vec![1, 2, 3]);
+        let node_ptr = tree.search_tree_by_value(&3).unwrap();
+        let handle = tree.handle(&node_ptr).unwrap();
+        tree.delete_node(std::rc::Rc::clone(&node_ptr)).unwrap();
+        assert_eq!(tree.resolve(&handle).err(), Some(TreeError::StaleHandle));
+    }
+
+    #[test]
+    fn test_handle_after_clear() {
+        let mut tree = build_free_from_values(vec![1, 2, 3]);
+        let handle = tree.find_handle(&1).unwrap();
+        tree.clear();
+        assert_eq!(tree.resolve(&handle).err(), Some(TreeError::StaleHandle));
+    }
+
+    #[test]
+    fn test_foreign_handle_is_rejected() {
+        let tree = build_free_from_values(vec![1, 2, 3]);
+        let mut other_tree = build_free_from_values(vec![1, 2, 3]);
+        let handle = tree.find_handle(&2).unwrap();
+        assert_eq!(
+            other_tree.resolve(&handle).err(),
+            Some(TreeError::ForeignNode)
+        );
+        assert_eq!(
+            other_tree.remove_by_handle(&handle),
+            Err(TreeError::ForeignNode)
+        );
+        assert_eq!(other_tree.len(), 3);
+        let node_ptr = tree.search_tree_by_value(&2).unwrap();
+        assert_eq!(
+            other_tree.handle(&node_ptr).err(),
+            Some(TreeError::ForeignNode)
+        );
+    }
+
+    #[test]
+    fn test_handle_to_counted_node() {
+        let mut tree = IsizeAvlTree::new().with_duplicate_policy(DuplicatePolicy::Count);
+        for value in [5, 5, 7] {
+            tree.insert(Node::new(0, value)).unwrap();
+        }
+        let handle = tree.find_handle(&5).unwrap();
+        assert_eq!(tree.remove_by_handle(&handle), Ok((0, 5)));
+        assert!(tree.resolve(&handle).is_ok());
+        assert_eq!(tree.remove_by_handle(&handle), Ok((0, 5)));
+        assert_eq!(tree.resolve(&handle).err(), Some(TreeError::StaleHandle));
+    }
+}
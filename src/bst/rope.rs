@@ -0,0 +1,324 @@
+use crate::bst::avl_sequence::AvlSequence;
+use crate::bst::avl_tree::{self, EntryRef};
+use crate::bst::summary::Summary;
+use std::cell::Ref;
+use std::fmt;
+use std::ops::Range;
+
+// Longest chunk the rope builds, in bytes
+pub(crate) const MAX_CHUNK_BYTES: usize = 512;
+
+// Chunks below this many bytes are merged into the next edit that touches them
+pub(crate) const MIN_CHUNK_BYTES: usize = MAX_CHUNK_BYTES / 2;
+
+// Sizes of a run of text; lines counts line breaks, so a text has one more line than that
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextSummary {
+    pub bytes: usize,
+    pub chars: usize,
+    pub lines: usize,
+}
+
+// Text buffer over AvlSequence: the text is cut into non-empty UTF-8 chunks of at most
+// MAX_CHUNK_BYTES, and every subtree keeps the TextSummary of its chunks, so char and line
+// offsets are found in O(log n) and an edit only rewrites the chunks it touches. Offsets are
+// counted in chars
+#[derive(Debug, Default)]
+pub struct Rope {
+    chunks: AvlSequence<String, TextSummary>,
+}
+
+// Walks the chunks of a Rope in order, lending each one through a ChunkRef
+pub struct Chunks<'a> {
+    inner: avl_tree::Iter<'a, (), String, TextSummary>,
+}
+
+// Read access to one chunk of a Rope, borrowed from its node instead of copied out
+pub struct ChunkRef<'a> {
+    entry: EntryRef<'a, (), String, TextSummary>,
+}
+
+impl TextSummary {
+    pub fn of(text: &str) -> TextSummary {
+        TextSummary {
+            bytes: text.len(),
+            chars: text.chars().count(),
+            lines: text.matches('\n').count(),
+        }
+    }
+}
+
+impl<'a> ChunkRef<'a> {
+    pub fn as_str(&self) -> Ref<'_, str> {
+        Ref::map(self.entry.index(), String::as_str)
+    }
+}
+
+impl Summary<(), String> for TextSummary {
+    fn identity() -> TextSummary {
+        TextSummary::default()
+    }
+
+    fn from_entry(chunk: &String, _value: &(), count: usize) -> TextSummary {
+        let summary = TextSummary::of(chunk);
+        TextSummary {
+            bytes: summary.bytes * count,
+            chars: summary.chars * count,
+            lines: summary.lines * count,
+        }
+    }
+
+    fn combine(left: &TextSummary, right: &TextSummary) -> TextSummary {
+        TextSummary {
+            bytes: left.bytes + right.bytes,
+            chars: left.chars + right.chars,
+            lines: left.lines + right.lines,
+        }
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Rope {
+        Rope {
+            chunks: split_into_chunks(text),
+        }
+    }
+}
+
+impl Rope {
+    pub fn new() -> Rope {
+        Rope::default()
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        self.chunks.summary().bytes
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.chunks.summary().chars
+    }
+
+    pub fn len_lines(&self) -> usize {
+        self.chunks.summary().lines + 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks {
+            inner: self.chunks.entries(),
+        }
+    }
+
+    // Inserts text so that it starts at char_offset. Panics when char_offset is past the end
+    pub fn insert(&mut self, char_offset: usize, text: &str) {
+        self.assert_offset(char_offset);
+        if text.is_empty() {
+            return;
+        }
+        // An offset at the very end extends the last chunk, if any
+        let anchor = if char_offset == self.len_chars() {
+            char_offset.checked_sub(1)
+        } else {
+            Some(char_offset)
+        };
+        let (position, chunk, summary_before) = match anchor {
+            Some(anchor) => self.chunk_at(anchor),
+            None => (0, String::new(), TextSummary::default()),
+        };
+        let split_byte = byte_offset(&chunk, char_offset - summary_before.chars);
+        let mut edited = String::with_capacity(chunk.len() + text.len());
+        edited.push_str(&chunk[..split_byte]);
+        edited.push_str(text);
+        edited.push_str(&chunk[split_byte..]);
+        let end = if chunk.is_empty() {
+            position
+        } else {
+            position + 1
+        };
+        self.replace_chunks(position..end, &edited);
+    }
+
+    // Deletes the chars in char_range. Panics when the range is reversed or ends past the end
+    pub fn remove(&mut self, char_range: Range<usize>) {
+        self.assert_range(&char_range);
+        if char_range.is_empty() {
+            return;
+        }
+        let (first_position, first_chunk, first_summary_before) = self.chunk_at(char_range.start);
+        let (last_position, last_chunk, last_summary_before) = self.chunk_at(char_range.end - 1);
+        let head_end = byte_offset(&first_chunk, char_range.start - first_summary_before.chars);
+        let tail_start = byte_offset(&last_chunk, char_range.end - last_summary_before.chars);
+        let kept_head = &first_chunk[..head_end];
+        let kept_tail = &last_chunk[tail_start..];
+        self.replace_chunks(
+            first_position..last_position + 1,
+            &[kept_head, kept_tail].concat(),
+        );
+    }
+
+    // Char offset where line starts, counting lines from zero, or None past the last line
+    pub fn line_to_char(&self, line: usize) -> Option<usize> {
+        if line == 0 {
+            return Some(0);
+        }
+        // Line n starts right after the line break with index n - 1
+        let (position, summary_before) = self.chunks.seek_by(line - 1, |summary| summary.lines)?;
+        let chunk = self.chunks.get(position)?;
+        let break_offset = chunk
+            .chars()
+            .enumerate()
+            .filter(|(_, char)| *char == '\n')
+            .nth(line - 1 - summary_before.lines)?
+            .0;
+        Some(summary_before.chars + break_offset + 1)
+    }
+
+    // Line holding char_offset; the end of the text belongs to the last line. Panics when
+    // char_offset is past the end
+    pub fn char_to_line(&self, char_offset: usize) -> usize {
+        self.assert_offset(char_offset);
+        if char_offset == self.len_chars() {
+            return self.len_lines() - 1;
+        }
+        let (_, chunk, summary_before) = self.chunk_at(char_offset);
+        summary_before.lines
+            + chunk
+                .chars()
+                .take(char_offset - summary_before.chars)
+                .filter(|char| *char == '\n')
+                .count()
+    }
+
+    // Copies out the chars in char_range. Panics when the range is reversed or ends past the
+    // end
+    pub fn slice(&self, char_range: Range<usize>) -> String {
+        self.assert_range(&char_range);
+        let mut sliced = String::new();
+        if char_range.is_empty() {
+            return sliced;
+        }
+        let (mut position, mut chunk, summary_before) = self.chunk_at(char_range.start);
+        let mut chars_before = summary_before.chars;
+        loop {
+            let chunk_chars = chunk.chars().count();
+            let start = char_range.start.saturating_sub(chars_before);
+            let end = (char_range.end - chars_before).min(chunk_chars);
+            sliced.push_str(&chunk[byte_offset(&chunk, start)..byte_offset(&chunk, end)]);
+            chars_before += chunk_chars;
+            position += 1;
+            if chars_before >= char_range.end {
+                return sliced;
+            }
+            chunk = self.chunks.get(position).unwrap();
+        }
+    }
+
+    // Chunk holding the char at char_offset, with its position and the summary of the text
+    // before it; char_offset must be inside the text
+    fn chunk_at(&self, char_offset: usize) -> (usize, String, TextSummary) {
+        let (position, summary_before) = self
+            .chunks
+            .seek_by(char_offset, |summary| summary.chars)
+            .unwrap();
+        let chunk = self.chunks.get(position).unwrap();
+        (position, chunk, summary_before)
+    }
+
+    // Swaps the chunks at positions for text cut into fresh chunks, splitting the sequence
+    // around them so the cost does not grow with the chunks left alone. An undersized
+    // neighbour, or any neighbour of an undersized text, is cut up along with the text, so
+    // repeated small edits don't leave a trail of tiny chunks behind
+    fn replace_chunks(&mut self, mut positions: Range<usize>, text: &str) {
+        let mut merged = String::new();
+        if positions.start > 0 {
+            let previous = self.chunks.get(positions.start - 1).unwrap();
+            if previous.len() < MIN_CHUNK_BYTES || text.len() < MIN_CHUNK_BYTES {
+                positions.start -= 1;
+                merged = previous;
+            }
+        }
+        merged.push_str(text);
+        if positions.end < self.chunks.len() {
+            let next = self.chunks.get(positions.end).unwrap();
+            if next.len() < MIN_CHUNK_BYTES || merged.len() < MIN_CHUNK_BYTES {
+                positions.end += 1;
+                merged.push_str(&next);
+            }
+        }
+        let chunks = std::mem::take(&mut self.chunks);
+        let (front, rest) = chunks.split_at(positions.start);
+        let (_, back) = rest.split_at(positions.end - positions.start);
+        self.chunks = front.concat(split_into_chunks(&merged)).concat(back);
+    }
+
+    fn assert_range(&self, char_range: &Range<usize>) {
+        assert!(
+            char_range.start <= char_range.end,
+            "char range starts at {} but ends at {}",
+            char_range.start,
+            char_range.end
+        );
+        self.assert_offset(char_range.end);
+    }
+
+    fn assert_offset(&self, char_offset: usize) {
+        assert!(
+            char_offset <= self.len_chars(),
+            "char offset {} is past the end of a rope of {} chars",
+            char_offset,
+            self.len_chars()
+        );
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(&chunk.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = ChunkRef<'a>;
+
+    fn next(&mut self) -> Option<ChunkRef<'a>> {
+        self.inner.next().map(|entry| ChunkRef { entry })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for Chunks<'a> {}
+
+// Byte where the char with the given index starts, or the length when it is one past the end
+fn byte_offset(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map_or(text.len(), |(byte, _)| byte)
+}
+
+// Cuts text into as few chunks of at most MAX_CHUNK_BYTES as it takes, never inside a char.
+// The chunks are about the same size, so none falls below MIN_CHUNK_BYTES by more than a char
+// unless the whole text does
+fn split_into_chunks(text: &str) -> AvlSequence<String, TextSummary> {
+    let mut chunks = AvlSequence::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let chunks_left = rest.len().div_ceil(MAX_CHUNK_BYTES);
+        let mut cut = rest.len().div_ceil(chunks_left);
+        while !rest.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        let (chunk, remainder) = rest.split_at(cut);
+        chunks.push_back(chunk.to_string());
+        rest = remainder;
+    }
+    chunks
+}
@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use super::super::rope::*;
+
+    // Small deterministic generator so the edit mix is the same on every run
+    fn next_random(state: &mut u64) -> usize {
+        *state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (*state >> 33) as usize
+    }
+
+    fn char_slice(text: &str, start: usize, end: usize) -> String {
+        text.chars().skip(start).take(end - start).collect()
+    }
+
+    fn assert_matches(rope: &Rope, model: &str) {
+        assert_eq!(rope.to_string(), model);
+        assert_eq!(rope.len_bytes(), model.len());
+        assert_eq!(rope.len_chars(), model.chars().count());
+        assert_eq!(rope.len_lines(), model.matches('\n').count() + 1);
+        assert!(rope
+            .chunks()
+            .all(|chunk| !chunk.as_str().is_empty() && chunk.as_str().len() <= MAX_CHUNK_BYTES));
+    }
+
+    #[test]
+    fn test_insert_and_remove_at_char_offsets() {
+        let mut rope = Rope::from("héllo wörld");
+        rope.insert(5, ",");
+        rope.insert(0, "¡");
+        rope.insert(rope.len_chars(), "!");
+        assert_matches(&rope, "¡héllo, wörld!");
+        rope.remove(1..8);
+        assert_matches(&rope, "¡wörld!");
+        rope.remove(0..rope.len_chars());
+        assert_matches(&rope, "");
+        assert!(rope.is_empty());
+        rope.insert(0, "again");
+        assert_matches(&rope, "again");
+    }
+
+    #[test]
+    fn test_random_edits_match_a_string() {
+        let pieces = ["a", "bc", "é", "\n", "日本語", "line\nbreak\n", "🦀"];
+        let mut state = 3;
+        let mut rope = Rope::new();
+        let mut model = String::new();
+        for step in 0..1500 {
+            let model_chars = model.chars().count();
+            if !next_random(&mut state).is_multiple_of(4) || model_chars == 0 {
+                let offset = next_random(&mut state) % (model_chars + 1);
+                let piece = pieces[next_random(&mut state) % pieces.len()].repeat(step % 40 + 1);
+                rope.insert(offset, &piece);
+                let byte = model
+                    .char_indices()
+                    .nth(offset)
+                    .map_or(model.len(), |(byte, _)| byte);
+                model.insert_str(byte, &piece);
+            } else {
+                let start = next_random(&mut state) % model_chars;
+                let end = start + next_random(&mut state) % (model_chars - start).min(300) + 1;
+                rope.remove(start..end);
+                model = char_slice(&model, 0, start) + &char_slice(&model, end, model_chars);
+            }
+            if step % 50 == 0 {
+                assert_matches(&rope, &model);
+            }
+        }
+        assert_matches(&rope, &model);
+        assert!(rope.chunks().len() > 1);
+    }
+
+    #[test]
+    fn test_slice_across_chunks() {
+        let model: String = (0..3000)
+            .map(|step| ['x', 'ö', '\n', '語'][step % 4])
+            .collect();
+        let rope = Rope::from(model.as_str());
+        assert!(rope.chunks().len() > 1);
+        for (start, end) in [
+            (0, 0),
+            (0, 3000),
+            (10, 20),
+            (100, 1900),
+            (2999, 3000),
+            (511, 1025),
+        ] {
+            assert_eq!(rope.slice(start..end), char_slice(&model, start, end));
+        }
+    }
+
+    #[test]
+    fn test_line_and_char_offsets() {
+        let model: String = (0..400)
+            .map(|line| format!("{}: {}\n", line, "ü".repeat(line % 13)))
+            .collect();
+        let rope = Rope::from(model.as_str());
+        let mut line_starts = vec![0];
+        for (offset, char) in model.chars().enumerate() {
+            if char == '\n' {
+                line_starts.push(offset + 1);
+            }
+        }
+        assert_eq!(rope.len_lines(), line_starts.len());
+        for (line, start) in line_starts.iter().enumerate() {
+            assert_eq!(rope.line_to_char(line), Some(*start));
+            assert_eq!(rope.char_to_line(*start), line);
+        }
+        assert_eq!(rope.line_to_char(line_starts.len()), None);
+        for offset in (0..=rope.len_chars()).step_by(7) {
+            let expected = line_starts.partition_point(|start| *start <= offset) - 1;
+            assert_eq!(rope.char_to_line(offset), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "char offset 4 is past the end of a rope of 3 chars")]
+    fn test_insert_past_the_end_panics() {
+        let mut rope = Rope::from("abc");
+        rope.insert(4, "d");
+    }
+
+    #[test]
+    fn test_small_deletes_merge_chunks() {
+        let mut model: Vec<char> = (0..8_000)
+            .map(|step| ['a', 'ß', '\n', '€'][step % 4])
+            .collect();
+        let mut rope = Rope::from(model.iter().collect::<String>().as_str());
+        let mut state = 11;
+        while model.len() > 800 {
+            let start = next_random(&mut state) % model.len();
+            let end = (start + 1 + next_random(&mut state) % 3).min(model.len());
+            rope.remove(start..end);
+            model.drain(start..end);
+            // Undersized chunks never sit next to each other, so they can't pile up
+            assert!(rope.chunks().len() <= rope.len_bytes() / (MIN_CHUNK_BYTES / 2) + 1);
+        }
+        assert_matches(&rope, &model.iter().collect::<String>());
+    }
+}
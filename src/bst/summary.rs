@@ -0,0 +1,142 @@
+use std::cmp::{max, min};
+use std::ops::Add;
+
+// A monoid folded over every subtree: each node keeps the combination, in sorted order, of
+// its left subtree's summary, the summary of its own entry and its right subtree's summary.
+// identity must leave any summary unchanged when combined with it, and combine must be
+// associative, so rotations never change the summary of a subtree
+pub trait Summary<K, V>: Clone {
+    fn identity() -> Self;
+    // count is how many equal entries the node stands for under DuplicatePolicy::Count
+    fn from_entry(index: &V, value: &K, count: usize) -> Self;
+    fn combine(left: &Self, right: &Self) -> Self;
+}
+
+// The summary of trees that don't need one
+impl<K, V> Summary<K, V> for () {
+    fn identity() {}
+
+    fn from_entry(_index: &V, _value: &K, _count: usize) {}
+
+    fn combine(_left: &(), _right: &()) {}
+}
+
+// Two summaries kept side by side, e.g. a sum and a count for averages
+impl<K, V, A: Summary<K, V>, B: Summary<K, V>> Summary<K, V> for (A, B) {
+    fn identity() -> (A, B) {
+        (A::identity(), B::identity())
+    }
+
+    fn from_entry(index: &V, value: &K, count: usize) -> (A, B) {
+        (
+            A::from_entry(index, value, count),
+            B::from_entry(index, value, count),
+        )
+    }
+
+    fn combine(left: &(A, B), right: &(A, B)) -> (A, B) {
+        (A::combine(&left.0, &right.0), B::combine(&left.1, &right.1))
+    }
+}
+
+// Number of entries, counting every repeat of a counted node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[allow(dead_code)]
+pub struct Count(pub usize);
+
+// Total of the indexes, an index counted several times being added that many times
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[allow(dead_code)]
+pub struct IndexSum<V>(pub V);
+
+// Smallest index, None when there are no entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct IndexMin<V>(pub Option<V>);
+
+// Largest index, None when there are no entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct IndexMax<V>(pub Option<V>);
+
+impl<K, V> Summary<K, V> for Count {
+    fn identity() -> Count {
+        Count(0)
+    }
+
+    fn from_entry(_index: &V, _value: &K, count: usize) -> Count {
+        Count(count)
+    }
+
+    fn combine(left: &Count, right: &Count) -> Count {
+        Count(left.0 + right.0)
+    }
+}
+
+// Default stands for zero
+impl<K, V: Clone + Default + Add<Output = V>> Summary<K, V> for IndexSum<V> {
+    fn identity() -> IndexSum<V> {
+        IndexSum(V::default())
+    }
+
+    // Adds index to itself by doubling, so a count of n takes O(log n) additions
+    fn from_entry(index: &V, _value: &K, count: usize) -> IndexSum<V> {
+        let mut sum = V::default();
+        let mut power = index.clone();
+        let mut remaining = count;
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                sum = sum + power.clone();
+            }
+            remaining >>= 1;
+            if remaining > 0 {
+                power = power.clone() + power;
+            }
+        }
+        IndexSum(sum)
+    }
+
+    fn combine(left: &IndexSum<V>, right: &IndexSum<V>) -> IndexSum<V> {
+        IndexSum(left.0.clone() + right.0.clone())
+    }
+}
+
+impl<K, V: Clone + Ord> Summary<K, V> for IndexMin<V> {
+    fn identity() -> IndexMin<V> {
+        IndexMin(None)
+    }
+
+    fn from_entry(index: &V, _value: &K, _count: usize) -> IndexMin<V> {
+        IndexMin(Some(index.clone()))
+    }
+
+    fn combine(left: &IndexMin<V>, right: &IndexMin<V>) -> IndexMin<V> {
+        match (&left.0, &right.0) {
+            (Some(left_index), Some(right_index)) => {
+                IndexMin(Some(min(left_index, right_index).clone()))
+            }
+            (Some(_), None) => left.clone(),
+            (None, _) => right.clone(),
+        }
+    }
+}
+
+impl<K, V: Clone + Ord> Summary<K, V> for IndexMax<V> {
+    fn identity() -> IndexMax<V> {
+        IndexMax(None)
+    }
+
+    fn from_entry(index: &V, _value: &K, _count: usize) -> IndexMax<V> {
+        IndexMax(Some(index.clone()))
+    }
+
+    fn combine(left: &IndexMax<V>, right: &IndexMax<V>) -> IndexMax<V> {
+        match (&left.0, &right.0) {
+            (Some(left_index), Some(right_index)) => {
+                IndexMax(Some(max(left_index, right_index).clone()))
+            }
+            (Some(_), None) => left.clone(),
+            (None, _) => right.clone(),
+        }
+    }
+}
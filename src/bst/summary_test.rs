@@ -0,0 +1,404 @@
+#[cfg(test)]
+mod tests {
+    use super::super::avl_cursor::*;
+    use super::super::avl_entry::Entry;
+    use super::super::avl_node::*;
+    use super::super::avl_tree::*;
+    use super::super::comparator::NaturalOrder;
+    use super::super::duplicate_policy::DuplicatePolicy;
+    use super::super::summary::{Count, IndexMax, IndexMin, IndexSum, Summary};
+    use std::cell::Cell;
+    use std::ops::Bound;
+
+    // Keeps every value in order, which only works out if summaries are combined in order,
+    // next to the sum of the indexes
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Trace {
+        values: Vec<isize>,
+        index_sum: isize,
+    }
+
+    impl Summary<isize, isize> for Trace {
+        fn identity() -> Trace {
+            Trace {
+                values: Vec::new(),
+                index_sum: 0,
+            }
+        }
+
+        fn from_entry(index: &isize, value: &isize, count: usize) -> Trace {
+            Trace {
+                values: vec![*value; count],
+                index_sum: index * count as isize,
+            }
+        }
+
+        fn combine(left: &Trace, right: &Trace) -> Trace {
+            let mut values = left.values.clone();
+            values.extend(&right.values);
+            Trace {
+                values,
+                index_sum: left.index_sum + right.index_sum,
+            }
+        }
+    }
+
+    type TraceTree = AvlTree<isize, isize, NaturalOrder, Trace>;
+
+    fn setup_trace_tree(duplicate_policy: DuplicatePolicy) -> TraceTree {
+        AvlTree::summarized(NaturalOrder).with_duplicate_policy(duplicate_policy)
+    }
+
+    // Recomputes every summary from scratch and compares it to the stored one
+    fn assert_summaries(node_ptr: &NodePtr<isize, isize, Trace>) -> Trace {
+        let node = node_ptr.borrow();
+        let left = match node.get_left_child() {
+            Some(left_child_ptr) => assert_summaries(&left_child_ptr),
+            None => Trace::identity(),
+        };
+        let right = match node.get_right_child() {
+            Some(right_child_ptr) => assert_summaries(&right_child_ptr),
+            None => Trace::identity(),
+        };
+        let own = Trace::from_entry(&node.index, &node.value, node.count);
+        assert_eq!(node.own_summary, own);
+        let expected = Trace::combine(&Trace::combine(&left, &own), &right);
+        assert_eq!(node.summary, expected);
+        expected
+    }
+
+    fn root_summary(tree: &TraceTree) -> Trace {
+        match tree.root.as_ref() {
+            Some(root_ptr) => assert_summaries(root_ptr),
+            None => Trace::identity(),
+        }
+    }
+
+    #[test]
+    fn test_summaries_follow_inserts_and_deletions() {
+        let mut tree = setup_trace_tree(DuplicatePolicy::KeepAll);
+        for value in 0..100 {
+            tree.insert(Node::new(value * 2, (value * 37) % 100))
+                .unwrap();
+            root_summary(&tree);
+        }
+        let summary = root_summary(&tree);
+        assert_eq!(summary.values, (0..100).collect::<Vec<_>>());
+        assert_eq!(summary.index_sum, (0..100).map(|value| value * 2).sum());
+
+        for value in (0..100).filter(|value| value % 3 != 0) {
+            tree.remove(&value).unwrap();
+            root_summary(&tree);
+        }
+        let summary = root_summary(&tree);
+        assert_eq!(
+            summary.values,
+            (0..100).filter(|value| value % 3 == 0).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            summary.index_sum,
+            (0..100)
+                .filter(|value| value % 3 == 0)
+                .map(|value| ((value * 73) % 100) * 2)
+                .sum()
+        );
+    }
+
+    #[test]
+    fn test_summaries_follow_pops() {
+        let mut tree = setup_trace_tree(DuplicatePolicy::KeepAll);
+        for value in 0..40 {
+            tree.insert(Node::new(1, value)).unwrap();
+        }
+        while tree.pop_first().is_some() {
+            if tree.pop_last().is_none() {
+                break;
+            }
+            root_summary(&tree);
+        }
+        assert_eq!(root_summary(&tree), Trace::identity());
+    }
+
+    #[test]
+    fn test_summaries_follow_counts() {
+        let mut tree = setup_trace_tree(DuplicatePolicy::Count);
+        for value in [5, 3, 5, 8, 5, 3] {
+            tree.insert(Node::new(10, value)).unwrap();
+            root_summary(&tree);
+        }
+        let summary = root_summary(&tree);
+        assert_eq!(summary.values, vec![3, 3, 5, 5, 5, 8]);
+        assert_eq!(summary.index_sum, 60);
+
+        tree.remove(&5).unwrap();
+        let summary = root_summary(&tree);
+        assert_eq!(summary.values, vec![3, 3, 5, 5, 8]);
+        assert_eq!(summary.index_sum, 50);
+    }
+
+    #[test]
+    fn test_summaries_follow_replaced_indexes() {
+        let mut tree = setup_trace_tree(DuplicatePolicy::Replace);
+        for value in 0..10 {
+            tree.insert(Node::new(1, value)).unwrap();
+        }
+        tree.insert(Node::new(100, 4)).unwrap();
+        assert_eq!(root_summary(&tree).index_sum, 109);
+    }
+
+    #[test]
+    fn test_summaries_follow_entries() {
+        let mut tree = setup_trace_tree(DuplicatePolicy::Reject);
+        for value in 0..10 {
+            tree.entry(value).or_insert(1);
+        }
+        assert_eq!(root_summary(&tree).index_sum, 10);
+
+        *tree.entry(3).or_insert(0).get_mut() = 20;
+        assert_eq!(root_summary(&tree).index_sum, 29);
+
+        tree.entry(7).and_modify(|index| *index += 5);
+        assert_eq!(root_summary(&tree).index_sum, 34);
+
+        match tree.entry(0) {
+            Entry::Occupied(mut entry) => assert_eq!(entry.insert(-1), 1),
+            Entry::Vacant(_) => panic!("0 is in the tree"),
+        }
+        assert_eq!(root_summary(&tree).index_sum, 32);
+    }
+
+    #[test]
+    fn test_summaries_follow_retain() {
+        let mut tree = setup_trace_tree(DuplicatePolicy::KeepAll);
+        for value in 0..30 {
+            tree.insert(Node::new(1, value)).unwrap();
+        }
+        tree.retain(|value, index| {
+            *index = *value;
+            value % 2 == 0
+        });
+        let summary = root_summary(&tree);
+        assert_eq!(
+            summary.values,
+            (0..30).filter(|value| value % 2 == 0).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            summary.index_sum,
+            (0..30).filter(|value| value % 2 == 0).sum()
+        );
+
+        let extracted: Vec<_> = tree
+            .extract_if(|value, index| {
+                *index = 0;
+                *value >= 20
+            })
+            .collect();
+        assert_eq!(extracted.len(), 5);
+        assert_eq!(root_summary(&tree).index_sum, 0);
+    }
+
+    #[test]
+    fn test_summaries_follow_cursor_inserts() {
+        let mut tree = setup_trace_tree(DuplicatePolicy::KeepAll);
+        for value in [10, 20, 30] {
+            tree.insert(Node::new(1, value)).unwrap();
+        }
+        let mut cursor: CursorMut<'_, isize, isize, NaturalOrder, Trace> =
+            tree.lower_bound_mut(&20);
+        cursor.insert_before(Node::new(2, 15)).unwrap();
+        cursor.insert_after(Node::new(3, 25)).unwrap();
+        let summary = root_summary(&tree);
+        assert_eq!(summary.values, vec![10, 15, 20, 25, 30]);
+        assert_eq!(summary.index_sum, 8);
+    }
+
+    #[test]
+    fn test_refresh_node_after_direct_mutation() {
+        let mut tree = setup_trace_tree(DuplicatePolicy::KeepAll);
+        for value in 0..20 {
+            tree.insert(Node::new(1, value)).unwrap();
+        }
+        let node_ptr = tree.search_tree_by_value(&13).unwrap();
+        node_ptr.borrow_mut().index = 50;
+        tree.refresh_node(&node_ptr);
+        assert_eq!(root_summary(&tree).index_sum, 69);
+    }
+
+    #[test]
+    fn test_unit_summary_is_the_default() {
+        let mut tree = build_free_from_values(vec![3, 1, 2]);
+        tree.insert(Node::new(0, 4)).unwrap();
+        let root_ptr = tree.root.as_ref().unwrap();
+        assert_eq!(root_ptr.borrow().summary, ());
+    }
+
+    type StatsTree = AvlTree<
+        isize,
+        isize,
+        NaturalOrder,
+        ((Count, IndexSum<isize>), (IndexMin<isize>, IndexMax<isize>)),
+    >;
+
+    fn all_bounds(value: isize) -> [Bound<isize>; 3] {
+        [
+            Bound::Included(value),
+            Bound::Excluded(value),
+            Bound::Unbounded,
+        ]
+    }
+
+    fn is_inside(value: isize, start: Bound<isize>, end: Bound<isize>) -> bool {
+        let after_start = match start {
+            Bound::Included(start_value) => value >= start_value,
+            Bound::Excluded(start_value) => value > start_value,
+            Bound::Unbounded => true,
+        };
+        let before_end = match end {
+            Bound::Included(end_value) => value <= end_value,
+            Bound::Excluded(end_value) => value < end_value,
+            Bound::Unbounded => true,
+        };
+        after_start && before_end
+    }
+
+    #[test]
+    fn test_aggregate_matches_a_fold_over_the_range() {
+        for duplicate_policy in [DuplicatePolicy::KeepAll, DuplicatePolicy::Count] {
+            let mut tree = setup_trace_tree(duplicate_policy);
+            let mut entries = Vec::new();
+            for step in 0..60 {
+                let value = (step * 7) % 23;
+                tree.insert(Node::new(step, value)).unwrap();
+                entries.push((step, value));
+            }
+            entries.sort_by_key(|&(_, value)| value);
+            if duplicate_policy == DuplicatePolicy::Count {
+                // A counted node keeps the index it was first inserted with
+                entries = entries
+                    .iter()
+                    .map(|&(_, value)| {
+                        let first = entries.iter().find(|entry| entry.1 == value).unwrap();
+                        (first.0, value)
+                    })
+                    .collect();
+            }
+            for start_value in -1..25 {
+                for end_value in start_value..25 {
+                    for start in all_bounds(start_value) {
+                        for end in all_bounds(end_value) {
+                            if start_value == end_value
+                                && matches!(start, Bound::Excluded(_))
+                                && matches!(end, Bound::Excluded(_))
+                            {
+                                continue;
+                            }
+                            let inside: Vec<_> = entries
+                                .iter()
+                                .filter(|&&(_, value)| is_inside(value, start, end))
+                                .collect();
+                            let aggregate = tree.aggregate((start, end));
+                            assert_eq!(
+                                aggregate.values,
+                                inside.iter().map(|entry| entry.1).collect::<Vec<_>>()
+                            );
+                            assert_eq!(
+                                aggregate.index_sum,
+                                inside.iter().map(|entry| entry.0).sum::<isize>()
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_aggregate_with_stock_summaries() {
+        let mut tree: StatsTree = AvlTree::summarized(NaturalOrder);
+        for value in 0..100 {
+            tree.insert(Node::new((value * 31) % 17, value)).unwrap();
+        }
+        let ((count, sum), (min, max)) = tree.aggregate(10..20);
+        let indexes: Vec<isize> = (10..20).map(|value| (value * 31) % 17).collect();
+        assert_eq!(count, Count(10));
+        assert_eq!(sum, IndexSum(indexes.iter().sum()));
+        assert_eq!(min, IndexMin(indexes.iter().min().copied()));
+        assert_eq!(max, IndexMax(indexes.iter().max().copied()));
+
+        let ((count, sum), (min, max)) = tree.aggregate(200..);
+        assert_eq!(count, Count(0));
+        assert_eq!(sum, IndexSum(0));
+        assert_eq!(min, IndexMin(None));
+        assert_eq!(max, IndexMax(None));
+
+        assert_eq!(tree.aggregate(..).0 .0, Count(100));
+    }
+
+    #[test]
+    fn test_stock_sum_repeats_counted_indexes() {
+        let mut tree: AvlTree<isize, isize, NaturalOrder, IndexSum<isize>> =
+            AvlTree::summarized(NaturalOrder).with_duplicate_policy(DuplicatePolicy::Count);
+        for _ in 0..4 {
+            tree.insert(Node::new(5, 1)).unwrap();
+        }
+        tree.insert(Node::new(2, 3)).unwrap();
+        assert_eq!(tree.aggregate(..=1), IndexSum(20));
+        assert_eq!(tree.aggregate(..), IndexSum(22));
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn test_aggregate_panics_on_reversed_bounds() {
+        let tree = setup_trace_tree(DuplicatePolicy::KeepAll);
+        tree.aggregate((Bound::Included(3), Bound::Included(1)));
+    }
+
+    thread_local! {
+        static FROM_ENTRY_CALLS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    // A Count that tallies how often an entry's own summary is built
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct CountedBuilds(usize);
+
+    impl Summary<isize, isize> for CountedBuilds {
+        fn identity() -> CountedBuilds {
+            CountedBuilds(0)
+        }
+
+        fn from_entry(_index: &isize, _value: &isize, count: usize) -> CountedBuilds {
+            FROM_ENTRY_CALLS.with(|calls| calls.set(calls.get() + 1));
+            CountedBuilds(count)
+        }
+
+        fn combine(left: &CountedBuilds, right: &CountedBuilds) -> CountedBuilds {
+            CountedBuilds(left.0 + right.0)
+        }
+    }
+
+    #[test]
+    fn test_own_summaries_are_built_once_per_change() {
+        let mut tree: AvlTree<isize, isize, NaturalOrder, CountedBuilds> =
+            AvlTree::summarized(NaturalOrder).with_duplicate_policy(DuplicatePolicy::Count);
+        FROM_ENTRY_CALLS.with(|calls| calls.set(0));
+        for value in 0..200 {
+            tree.insert(Node::new(0, value)).unwrap();
+        }
+        // Rebalancing and aggregating only combine what is stored
+        tree.aggregate(10..150);
+        assert_eq!(FROM_ENTRY_CALLS.with(Cell::get), 200);
+        // One build for the new node, one for the count it raises, one for the count lowered
+        tree.insert(Node::new(0, 7)).unwrap();
+        tree.remove(&7);
+        assert_eq!(FROM_ENTRY_CALLS.with(Cell::get), 203);
+        assert_eq!(tree.aggregate(..), CountedBuilds(200));
+    }
+
+    #[test]
+    fn test_stock_sum_of_large_counts() {
+        for count in [0, 1, 2, 3, 7, 8, 1000, 1 << 20] {
+            let IndexSum(sum) = <IndexSum<i64> as Summary<(), i64>>::from_entry(&3, &(), count);
+            assert_eq!(sum, 3 * count as i64);
+        }
+    }
+}
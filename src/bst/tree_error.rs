@@ -0,0 +1,52 @@
+use std::error::Error;
+use std::fmt;
+
+// Everything that can go wrong when the tree API is handed bad input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeError {
+    // The value list and the index list given to a builder differ in length
+    LengthMismatch { values_len: usize, index_len: usize },
+    // The operation needs at least one node
+    EmptyTree,
+    // The node handle belongs to a different tree
+    ForeignNode,
+    // A rotation needs a child the node does not have
+    MissingChild,
+    // The node handle was unlinked from the tree by an earlier deletion
+    StaleHandle,
+    // The key is already in a tree using DuplicatePolicy::Reject
+    DuplicateKey,
+    // A cursor was asked to insert a node whose value does not fit between its neighbours
+    OrderViolation,
+    // An interval tree was handed an interval whose end is not after its start
+    EmptyInterval,
+    // The node is still held through a NodePtr outside the tree, so its entry can't be moved out
+    NodeInUse,
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeError::LengthMismatch {
+                values_len,
+                index_len,
+            } => write!(
+                f,
+                "index list and value list must have the same length, got {} indexes for {} values",
+                index_len, values_len
+            ),
+            TreeError::EmptyTree => write!(f, "the tree is empty"),
+            TreeError::ForeignNode => write!(f, "the node does not belong to this tree"),
+            TreeError::MissingChild => write!(f, "the node has no child to rotate with"),
+            TreeError::StaleHandle => write!(f, "the node has already been removed from the tree"),
+            TreeError::DuplicateKey => write!(f, "the key is already in the tree"),
+            TreeError::OrderViolation => {
+                write!(f, "the value does not fit between the cursor's neighbours")
+            }
+            TreeError::EmptyInterval => write!(f, "the interval must end after it starts"),
+            TreeError::NodeInUse => write!(f, "the node is still held outside the tree"),
+        }
+    }
+}
+
+impl Error for TreeError {}